@@ -0,0 +1,12 @@
+use shared::Rng;
+
+/// Generate a list of `size` dial rotations
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+    for _ in 0..size {
+        let direction = if rng.chance(0.5) { 'L' } else { 'R' };
+        output.push_str(&format!("{}{}\n", direction, rng.between(1, 999)));
+    }
+
+    output
+}
@@ -0,0 +1,307 @@
+use crate::{Cursor, MAX_STEPS, Move, count_codes, modulo};
+use std::fmt;
+use std::str::FromStr;
+
+// Tables of reachable codes hold an entry per move and dial position, for each number of edits
+pub const MAX_DIAL_SIZE: i32 = 10_000;
+pub const DEFAULT_MAX_EDITS: usize = 10;
+
+/// Rule giving the code of a sequence of moves
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rule {
+    /// Number of moves landing on zero
+    Part1,
+    /// Number of clicks on zero
+    Part2,
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "1" => Ok(Rule::Part1),
+            "2" => Ok(Rule::Part2),
+            _ => Err(format!("unknown part `{}`", value)),
+        }
+    }
+}
+
+/// Edit of a sequence of moves, at an index of the original sequence
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Edit {
+    /// Insert a move before the one at the index (or at the end)
+    Insert(usize, Move),
+    /// Replace the move at the index
+    Change(usize, Move),
+}
+
+impl Edit {
+    fn index(&self) -> usize {
+        match self {
+            Edit::Insert(index, _) | Edit::Change(index, _) => *index,
+        }
+    }
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Insert(index, mv) => write!(f, "insert {} before move {}", mv, index),
+            Edit::Change(index, mv) => write!(f, "change move {} to {}", index, mv),
+        }
+    }
+}
+
+/// Codes reachable from a state, always contiguous: all codes between `min` and `max`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Codes {
+    min: usize,
+    max: usize,
+}
+
+impl Codes {
+    const NONE: Codes = Codes {
+        min: usize::MAX,
+        max: 0,
+    };
+
+    fn exactly(code: usize) -> Codes {
+        Codes {
+            min: code,
+            max: code,
+        }
+    }
+
+    fn is_empty(self) -> bool {
+        self.min > self.max
+    }
+
+    fn contains(self, code: usize) -> bool {
+        (self.min..=self.max).contains(&code)
+    }
+
+    fn union(self, other: Codes) -> Codes {
+        Codes {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Codes of a state followed by another one
+    fn plus(self, other: Codes) -> Codes {
+        if self.is_empty() || other.is_empty() {
+            return Codes::NONE;
+        }
+
+        Codes {
+            min: self.min + other.min,
+            max: self.max.saturating_add(other.max),
+        }
+    }
+}
+
+/// Tables of the codes reachable from each move index and dial position, for each number of edits
+struct Search<'a> {
+    moves: &'a [Move],
+    size: i32,
+    rule: Rule,
+    layers: Vec<Vec<Codes>>,
+}
+
+impl Search<'_> {
+    fn entry(&self, index: usize, position: i32) -> usize {
+        index * self.size as usize + position as usize
+    }
+
+    /// Code of the move at an index from a position, and the position after it
+    fn keep(&self, index: usize, position: i32) -> (usize, i32) {
+        let mut cursor = Cursor::new(position, self.size);
+        let (part1, part2) = count_codes(&mut cursor, &self.moves[index..=index], None);
+        let code = match self.rule {
+            Rule::Part1 => part1,
+            Rule::Part2 => part2,
+        };
+
+        (code, cursor.target)
+    }
+
+    /// Codes of a move chosen freely from a position to another one: part 1 only depends on the
+    /// landing position, while part 2 can add full turns
+    fn edit_codes(&self, from: i32, to: i32) -> Codes {
+        match self.rule {
+            Rule::Part1 => Codes::exactly((to == 0) as usize),
+            Rule::Part2 => {
+                // Reaching zero from elsewhere clicks on it once, other moves can avoid it
+                let min = (to == 0 && from != 0) as usize;
+                let max_turns = ((MAX_STEPS - self.size) / self.size) as usize;
+                Codes {
+                    min,
+                    max: min + max_turns,
+                }
+            }
+        }
+    }
+
+    /// Move from a position to another one clicking on zero `zero_count` times (part 2 only)
+    fn edit_move(&self, from: i32, to: i32, zero_count: usize) -> Move {
+        let extra_turns = match self.rule {
+            Rule::Part1 => 0,
+            Rule::Part2 => zero_count - self.edit_codes(from, to).min,
+        } as i32;
+
+        // Turn left only to avoid zero on the way
+        if to < from && to != 0 {
+            Move::Left(from - to + extra_turns * self.size)
+        } else {
+            Move::Right(modulo(to - from, self.size) + extra_turns * self.size)
+        }
+    }
+
+    /// Codes reachable with an edit from zero and from another position, continuing with the
+    /// moves from an index with one less edit
+    fn edited(&self, previous: &[Codes], index: usize) -> [Codes; 2] {
+        [0, 1].map(|from| {
+            (0..self.size).fold(Codes::NONE, |codes, to| {
+                let edited = self.edit_codes(from, to);
+                codes.union(edited.plus(previous[self.entry(index, to)]))
+            })
+        })
+    }
+
+    /// Compute the codes reachable with one more edit than the last layer
+    fn push_layer(&mut self) {
+        let move_count = self.moves.len();
+        let previous = self.layers.last().map(|layer| layer.as_slice());
+        let mut codes = vec![Codes::NONE; (move_count + 1) * self.size as usize];
+        for index in (0..=move_count).rev() {
+            let inserted = previous.map(|previous| self.edited(previous, index));
+            let changed = previous
+                .filter(|_| index < move_count)
+                .map(|previous| self.edited(previous, index + 1));
+
+            for position in 0..self.size {
+                let mut reachable = if index == move_count {
+                    Codes::exactly(0)
+                } else {
+                    let (code, next) = self.keep(index, position);
+                    Codes::exactly(code).plus(codes[self.entry(index + 1, next)])
+                };
+
+                for edited in [inserted, changed].into_iter().flatten() {
+                    reachable = reachable.union(edited[(position != 0) as usize]);
+                }
+
+                codes[self.entry(index, position)] = reachable;
+            }
+        }
+
+        self.layers.push(codes);
+    }
+
+    /// Follow the tables to find edits producing a code, with a given number of edits
+    fn edits(&self, start: i32, code: usize, mut edit_count: usize) -> Vec<Edit> {
+        let mut edits = vec![];
+        let (mut index, mut position, mut remaining) = (0, start, code);
+        loop {
+            if index == self.moves.len() && remaining == 0 {
+                return edits;
+            }
+
+            // Keep the original move as long as the code can be reached
+            if index < self.moves.len() {
+                let (code, next) = self.keep(index, position);
+                if remaining >= code
+                    && self.layers[edit_count][self.entry(index + 1, next)]
+                        .contains(remaining - code)
+                {
+                    index += 1;
+                    position = next;
+                    remaining -= code;
+                    continue;
+                }
+            }
+
+            // Otherwise change the move or insert one, to a position from which the rest of the
+            // code can be reached
+            edit_count -= 1;
+            let previous = &self.layers[edit_count];
+            let changes = (index < self.moves.len()).then_some((index + 1, true));
+            let (next_index, is_change, to, zero_count) = changes
+                .into_iter()
+                .chain([(index, false)])
+                .flat_map(|(next_index, is_change)| {
+                    (0..self.size).map(move |to| (next_index, is_change, to))
+                })
+                .find_map(|(next_index, is_change, to)| {
+                    let edited = self.edit_codes(position, to);
+                    let rest = previous[self.entry(next_index, to)];
+                    edited.plus(rest).contains(remaining).then(|| {
+                        let zero_count = edited.min.max(remaining.saturating_sub(rest.max));
+                        (next_index, is_change, to, zero_count)
+                    })
+                })
+                .expect("edits should reach a code found in the tables");
+
+            let mv = self.edit_move(position, to, zero_count);
+            edits.push(if is_change {
+                Edit::Change(index, mv)
+            } else {
+                Edit::Insert(index, mv)
+            });
+            index = next_index;
+            position = to;
+            remaining -= zero_count;
+        }
+    }
+}
+
+/// Fewest edits (at most `max_edits`) so that moves from a cursor produce a code under a rule,
+/// `None` if more edits are needed
+pub fn find_edits(
+    cursor: &Cursor,
+    moves: &[Move],
+    rule: Rule,
+    code: usize,
+    max_edits: usize,
+) -> Option<Vec<Edit>> {
+    let mut search = Search {
+        moves,
+        size: cursor.size,
+        rule,
+        layers: vec![],
+    };
+
+    for edit_count in 0..=max_edits {
+        search.push_layer();
+        if search.layers[edit_count][search.entry(0, cursor.target)].contains(code) {
+            return Some(search.edits(cursor.target, code, edit_count));
+        }
+    }
+
+    None
+}
+
+/// Moves after applying edits, sorted by index
+pub fn apply_edits(moves: &[Move], edits: &[Edit]) -> Vec<Move> {
+    let mut edited = vec![];
+    let mut edits = edits.iter().peekable();
+    for index in 0..=moves.len() {
+        let mut is_changed = false;
+        while let Some(edit) = edits.next_if(|edit| edit.index() == index) {
+            match edit {
+                Edit::Insert(_, mv) => edited.push(*mv),
+                Edit::Change(_, mv) => {
+                    edited.push(*mv);
+                    is_changed = true;
+                }
+            }
+        }
+
+        if index < moves.len() && !is_changed {
+            edited.push(moves[index]);
+        }
+    }
+
+    edited
+}
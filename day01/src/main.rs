@@ -1,6 +1,143 @@
+mod generator;
+mod inverse;
+#[cfg(test)]
+mod reference;
+
+use inverse::Rule;
+use shared::args::{Args, ArgsError};
+use shared::fuzz;
+use shared::input::{self, InputError, LineReader, TrailingWhitespace};
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::thread;
+
+// Larger moves or dials would overflow the cursor computations
+const MAX_STEPS: i32 = 1_000_000_000;
+const MAX_DIAL_SIZE: i32 = 1_000_000_000;
+
+// Keep repeated moves from expanding into huge move lists
+const MAX_REPEAT: usize = 10_000;
+
+// Below this number of moves per thread, threads cost more than they save
+const MIN_CHUNK_LEN: usize = 100_000;
+
+// Moves read at once when streaming an input, to evaluate them in parallel (chunk summaries hold
+// a few integers per move)
+const STREAM_BATCH_LEN: usize = 1_000_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 enum Move {
     Left(i32),
     Right(i32),
+    Set(i32),
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Left(steps) => write!(f, "L{}", steps),
+            Move::Right(steps) => write!(f, "R{}", steps),
+            Move::Set(value) => write!(f, "={}", value),
+        }
+    }
+}
+
+/// Dial of a safe: positions from 0 to `size - 1`, the cursor pointing at `start` first
+struct Dial {
+    size: i32,
+    start: i32,
+}
+
+impl Default for Dial {
+    fn default() -> Self {
+        Dial {
+            size: 100,
+            start: 50,
+        }
+    }
+}
+
+impl Dial {
+    /// Dial given by `--dial-size=N` and `--start=N` on the command line (with default values,
+    /// the cursor starting halfway round the dial)
+    fn from_args(args: &Args) -> Result<Dial, ArgsError> {
+        let size = args.value_or("dial-size", Dial::default().size)?;
+        let start = args.value_or("start", size / 2)?;
+
+        if !(1..=MAX_DIAL_SIZE).contains(&size) {
+            return Err(ArgsError {
+                message: format!("dial size must be between 1 and {}", MAX_DIAL_SIZE),
+            });
+        }
+
+        if !(0..size).contains(&start) {
+            return Err(ArgsError {
+                message: format!("start position must be between 0 and {}", size - 1),
+            });
+        }
+
+        Ok(Dial { size, start })
+    }
+
+    fn cursor(&self) -> Cursor {
+        Cursor::new(self.start, self.size)
+    }
+}
+
+/// What happened during a move
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Event {
+    /// The cursor pointed at zero this many times, including at the end of the move
+    PassedZero(usize),
+    /// The cursor went this many times from the last position to zero, or the other way around
+    Wrapped(usize),
+    LandedOnZero,
+    /// Final position, always the last event of a move
+    Position(i32),
+}
+
+/// Effect of a sequence of moves on the cursor position. Shifts compose associatively, so that
+/// the start position of any chunk of moves can be found without simulating the moves before it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Shift {
+    /// Move by an offset (between 0 and the dial size)
+    Offset(i32),
+    /// Set the position, whatever it was
+    Set(i32),
+}
+
+impl Shift {
+    fn of(mv: &Move, size: i32) -> Shift {
+        match mv {
+            Move::Left(steps) => Shift::Offset(modulo(-steps, size)),
+            Move::Right(steps) => Shift::Offset(modulo(*steps, size)),
+            Move::Set(value) => Shift::Set(*value),
+        }
+    }
+
+    /// Shift of the moves of `self` followed by the moves of `other`
+    fn then(self, other: Shift, size: i32) -> Shift {
+        match (self, other) {
+            (Shift::Offset(offset1), Shift::Offset(offset2)) => {
+                Shift::Offset(modulo(offset1 + offset2, size))
+            }
+            (Shift::Set(value), Shift::Offset(offset)) => Shift::Set(modulo(value + offset, size)),
+            (_, Shift::Set(value)) => Shift::Set(value),
+        }
+    }
+
+    fn apply(self, position: i32, size: i32) -> i32 {
+        match self {
+            Shift::Offset(offset) => modulo(position + offset, size),
+            Shift::Set(value) => value,
+        }
+    }
 }
 
 struct Cursor {
@@ -20,13 +157,6 @@ impl Cursor {
         }
     }
 
-    fn mv(&mut self, mv: &Move) {
-        match mv {
-            Move::Left(steps) => self.target = modulo(self.target - steps, self.size),
-            Move::Right(steps) => self.target = modulo(self.target + steps, self.size),
-        }
-    }
-
     fn mv_with_zero_count(&mut self, mv: &Move) -> i32 {
         match mv {
             Move::Left(steps) => {
@@ -44,66 +174,417 @@ impl Cursor {
 
                 zero_count
             }
+            Move::Set(value) => {
+                // No click on the way, but ending on zero counts like for a rotation
+                self.target = *value;
+                (*value == 0) as i32
+            }
+        }
+    }
+
+    fn wrap_count(&self, mv: &Move) -> i32 {
+        match mv {
+            // From zero to the last position: first after `target + 1` steps, then every turn
+            Move::Left(steps) if *steps > self.target => (steps - self.target - 1) / self.size + 1,
+            Move::Left(_) | Move::Set(_) => 0,
+            Move::Right(steps) => (self.target + steps) / self.size,
+        }
+    }
+
+    /// Apply a move, returning what happened during it
+    fn step(&mut self, mv: &Move) -> impl Iterator<Item = Event> + use<> {
+        let wrap_count = self.wrap_count(mv);
+        let zero_count = self.mv_with_zero_count(mv);
+
+        [
+            (zero_count > 0).then_some(Event::PassedZero(zero_count as usize)),
+            (wrap_count > 0).then_some(Event::Wrapped(wrap_count as usize)),
+            (self.target == 0).then_some(Event::LandedOnZero),
+            Some(Event::Position(self.target)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// Apply moves in turn, returning the events of all of them
+    fn events<'a>(&'a mut self, moves: &'a [Move]) -> impl Iterator<Item = Event> + 'a {
+        moves.iter().flat_map(|mv| self.step(mv))
+    }
+}
+
+/// Parse a line made of a move (`L<steps>`, `R<steps>` or `=<position>`), optionally repeated
+/// with `x<count>`, and an optional `#` comment. Returns `None` for lines without any move.
+fn parse_line(line: &mut Parser, dial: &Dial) -> Result<Option<(Move, usize)>, ParseError> {
+    line.skip_spaces();
+    if line.is_empty() || line.try_literal("#") {
+        return Ok(None);
+    }
+
+    let mv = if line.try_literal("L") {
+        Move::Left(line.bounded(0..=MAX_STEPS)?)
+    } else if line.try_literal("R") {
+        Move::Right(line.bounded(0..=MAX_STEPS)?)
+    } else if line.try_literal("=") {
+        Move::Set(line.bounded(0..=dial.size - 1)?)
+    } else {
+        return Err(line.error("expected `L`, `R` or `=`"));
+    };
+
+    let repeat = if line.try_literal("x") {
+        line.bounded(1..=MAX_REPEAT)?
+    } else {
+        1
+    };
+
+    line.skip_spaces();
+    if !line.try_literal("#") {
+        line.end()?;
+    }
+
+    Ok(Some((mv, repeat)))
+}
+
+fn parse_input(raw_data: &str, dial: &Dial) -> Result<Vec<Move>, ParseError> {
+    let mut all_moves = vec![];
+    for mut line in Parser::new(raw_data).lines() {
+        if let Some((mv, repeat)) = parse_line(&mut line, dial)? {
+            all_moves.extend(std::iter::repeat_n(mv, repeat));
         }
     }
+
+    Ok(all_moves)
 }
 
-fn parse_input(raw_data: &str) -> Vec<Move> {
-    raw_data
-        .lines()
-        .map(|line| {
-            let (dir, count) = line.split_at(1);
-            let steps = count.trim().parse::<i32>().unwrap();
-            match dir {
-                "L" => Move::Left(steps),
-                "R" => Move::Right(steps),
-                _ => panic!("Unknown direction"),
+/// Number of moves ending on each position of the dial, positions never landed on being left out
+type Landings = HashMap<i32, usize>;
+
+/// Codes of both parts for a chunk of moves, and the positions they land on if requested, in a
+/// single simulation: the cursor ends on the position after the chunk
+fn count_codes(
+    cursor: &mut Cursor,
+    moves: &[Move],
+    mut landings: Option<&mut Landings>,
+) -> (usize, usize) {
+    let mut codes = (0, 0);
+    for event in cursor.events(moves) {
+        match event {
+            Event::LandedOnZero => codes.0 += 1,
+            Event::PassedZero(count) => codes.1 += count,
+            Event::Position(position) => {
+                if let Some(landings) = landings.as_deref_mut() {
+                    *landings.entry(position).or_default() += 1;
+                }
             }
-        })
-        .collect()
+            Event::Wrapped(_) => (),
+        }
+    }
+
+    codes
+}
+
+/// Effect of a chunk of moves from any start position, found with a single simulation of the
+/// chunk. Until its first `Set` move, the positions of the chunk only depend on the start position
+/// by an offset: clicks on zero and landings are kept as functions of the start position. From the
+/// first `Set` move, the chunk is simulated like any other.
+struct Summary {
+    size: i32,
+    shift: Shift,
+    /// Positions landed on before the first `Set` move, relative to the start position (sorted)
+    relative_landings: Vec<i32>,
+    /// Bounds of the start positions for which a move before the first `Set` one clicks on zero
+    /// once more (sorted, from `starts` included to `ends` excluded)
+    click_starts: Vec<i32>,
+    click_ends: Vec<i32>,
+    /// Codes counted whatever the start position
+    codes: (usize, usize),
+    /// Positions landed on from the first `Set` move, if requested
+    landings: Option<Landings>,
 }
 
-fn solve_part1(cursor: &mut Cursor, all_moves: &Vec<Move>) -> usize {
-    let mut code = 0;
-    for mv in all_moves {
-        cursor.mv(mv);
-        if cursor.target == 0 {
-            code += 1;
+impl Summary {
+    fn of(moves: &[Move], size: i32, with_landings: bool) -> Summary {
+        let mut summary = Summary {
+            size,
+            shift: Shift::Offset(0),
+            relative_landings: vec![],
+            click_starts: vec![],
+            click_ends: vec![],
+            codes: (0, 0),
+            landings: with_landings.then(Landings::new),
+        };
+
+        let set_index = moves
+            .iter()
+            .position(|mv| matches!(mv, Move::Set(_)))
+            .unwrap_or(moves.len());
+        for mv in &moves[..set_index] {
+            summary.add_move(mv);
+        }
+
+        // The position after a `Set` move does not depend on the start position
+        if set_index < moves.len() {
+            let mut cursor = Cursor::new(0, size);
+            let codes = count_codes(&mut cursor, &moves[set_index..], summary.landings.as_mut());
+            summary.codes = (summary.codes.0 + codes.0, summary.codes.1 + codes.1);
+            summary.shift = Shift::Set(cursor.target);
         }
+
+        summary.relative_landings.sort_unstable();
+        summary.click_starts.sort_unstable();
+        summary.click_ends.sort_unstable();
+        summary
     }
 
-    code
+    /// Add a rotation from the current offset: a turn of `k * size + r` steps clicks on zero `k`
+    /// times, plus once if the position before it is one of `r` positions
+    fn add_move(&mut self, mv: &Move) {
+        let Shift::Offset(offset) = self.shift else {
+            unreachable!("moves after a `Set` move are simulated");
+        };
+
+        let (steps, first_clicking) = match mv {
+            // Positions from `size - r` to `size - 1`
+            Move::Right(steps) => (*steps, self.size - steps % self.size),
+            // Positions from 1 to `r`
+            Move::Left(steps) => (*steps, 1),
+            Move::Set(_) => unreachable!("moves after a `Set` move are simulated"),
+        };
+        self.codes.1 += (steps / self.size) as usize;
+
+        // Start positions for which the position before the move is one of the clicking ones, a
+        // range which may wrap around the dial
+        let clicking_count = steps % self.size;
+        if clicking_count > 0 {
+            let start = modulo(first_clicking - offset, self.size);
+            let end = start + clicking_count;
+            if end <= self.size {
+                self.click_starts.push(start);
+                self.click_ends.push(end);
+            } else {
+                self.click_starts.extend([start, 0]);
+                self.click_ends.extend([self.size, end - self.size]);
+            }
+        }
+
+        self.shift = self.shift.then(Shift::of(mv, self.size), self.size);
+        let Shift::Offset(offset) = self.shift else {
+            unreachable!("rotations keep an offset");
+        };
+        self.relative_landings.push(offset);
+    }
+
+    /// Codes of the chunk from the position of a cursor, which ends on the position after the
+    /// chunk. Positions landed on are added to `landings` (if they were kept for the summary).
+    fn apply(&self, cursor: &mut Cursor, landings: Option<&mut Landings>) -> (usize, usize) {
+        let start = cursor.target;
+        let count_until =
+            |bounds: &[i32], position: i32| bounds.partition_point(|bound| *bound <= position);
+
+        // Landing on zero means landing `size - start` positions after the start
+        let zero_offset = modulo(-start, self.size);
+        let zero_landings = count_until(&self.relative_landings, zero_offset)
+            - count_until(&self.relative_landings, zero_offset - 1);
+        let clicks = count_until(&self.click_starts, start) - count_until(&self.click_ends, start);
+
+        if let Some(landings) = landings
+            && let Some(chunk_landings) = &self.landings
+        {
+            for offset in &self.relative_landings {
+                *landings
+                    .entry(modulo(start + offset, self.size))
+                    .or_default() += 1;
+            }
+            for (position, count) in chunk_landings {
+                *landings.entry(*position).or_default() += count;
+            }
+        }
+
+        cursor.target = self.shift.apply(start, self.size);
+        (self.codes.0 + zero_landings, self.codes.1 + clicks)
+    }
 }
 
-fn solve_part2(cursor: &mut Cursor, all_moves: &Vec<Move>) -> i32 {
-    let mut code = 0;
-    for mv in all_moves {
-        code += cursor.mv_with_zero_count(mv);
+/// Codes of both parts, moves being split in chunks summarized in parallel. Summaries are then
+/// applied in turn from the start position, which only takes a few operations per chunk.
+fn count_codes_parallel(
+    cursor: &mut Cursor,
+    moves: &[Move],
+    chunk_count: usize,
+    mut landings: Option<&mut Landings>,
+) -> (usize, usize) {
+    // A single chunk is simulated on the current thread
+    if chunk_count <= 1 {
+        return count_codes(cursor, moves, landings);
     }
 
-    code
+    let size = cursor.size;
+    let with_landings = landings.is_some();
+    let summaries: Vec<Summary> = thread::scope(|scope| {
+        let handles: Vec<_> = moves
+            .chunks(moves.len().div_ceil(chunk_count).max(1))
+            .map(|chunk| scope.spawn(move || Summary::of(chunk, size, with_landings)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    summaries.iter().fold((0, 0), |total, summary| {
+        let codes = summary.apply(cursor, landings.as_deref_mut());
+        (total.0 + codes.0, total.1 + codes.1)
+    })
+}
+
+/// Number of chunks to split moves into, given the number of available threads
+fn chunk_count(move_count: usize, thread_count: usize) -> usize {
+    thread_count.min(move_count / MIN_CHUNK_LEN).max(1)
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-01.txt");
-    let all_moves = parse_input(raw_data);
+/// Solve both parts in a single pass over the input, moves being parsed one at a time and
+/// evaluated in batches of about `STREAM_BATCH_LEN` moves, the only ones held in memory
+fn solve_stream(
+    reader: impl BufRead,
+    dial: &Dial,
+    thread_count: usize,
+    mut landings: Option<&mut Landings>,
+) -> Result<(usize, usize), InputError> {
+    let mut cursor = dial.cursor();
+    let mut codes = (0, 0);
+    let mut batch = vec![];
+
+    let mut lines = LineReader::new(reader, TrailingWhitespace::Trim);
+    loop {
+        let line = lines.next_line()?;
+        if let Some(mut line) = line
+            && let Some((mv, repeat)) = parse_line(&mut line, dial)?
+        {
+            batch.extend(std::iter::repeat_n(mv, repeat));
+        }
+
+        // Evaluate full batches, and the last one
+        let is_last = line.is_none();
+        if batch.len() >= STREAM_BATCH_LEN || is_last {
+            let chunk_count = chunk_count(batch.len(), thread_count);
+            let batch_codes =
+                count_codes_parallel(&mut cursor, &batch, chunk_count, landings.as_deref_mut());
+            codes = (codes.0 + batch_codes.0, codes.1 + batch_codes.1);
+            batch.clear();
+        }
+
+        if is_last {
+            return Ok(codes);
+        }
+    }
+}
+
+fn solve(
+    raw_data: &str,
+    dial: &Dial,
+    thread_count: usize,
+    landings: Option<&mut Landings>,
+) -> Result<(usize, usize), ParseError> {
+    let all_moves = parse_input(raw_data, dial)?;
+    let chunk_count = chunk_count(all_moves.len(), thread_count);
+    Ok(count_codes_parallel(
+        &mut dial.cursor(),
+        &all_moves,
+        chunk_count,
+        landings,
+    ))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-01.txt"),
+        TrailingWhitespace::Trim,
+    );
+    let dial = Dial::from_args(&args)?;
+    let available_threads = thread::available_parallelism().map_or(1, |count| count.get());
+    let thread_count = args.value_or("threads", available_threads)?.max(1);
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, |input| {
+        solve(input, &dial, thread_count, None)
+    })? {
+        return Ok(());
+    }
+
+    // Edit the moves to produce another code, and print them as a new input
+    if let Some(code) = args.value("target-code")? {
+        let rule = args.value_or("part", Rule::Part1)?;
+        let max_edits = args.value_or("max-edits", inverse::DEFAULT_MAX_EDITS)?;
+        if dial.size > inverse::MAX_DIAL_SIZE {
+            return Err(
+                format!("dials are limited to {} positions", inverse::MAX_DIAL_SIZE).into(),
+            );
+        }
+
+        let raw_data = match args.positional()[..] {
+            [path] => fs::read_to_string(path)?,
+            _ => raw_data,
+        };
+        let all_moves = parse_input(&raw_data, &dial)?;
+        let Some(edits) = inverse::find_edits(&dial.cursor(), &all_moves, rule, code, max_edits)
+        else {
+            return Err(format!("code {} needs more than {} edits", code, max_edits).into());
+        };
+
+        for edit in &edits {
+            eprintln!("  [edit] {}", edit);
+        }
+        for mv in inverse::apply_edits(&all_moves, &edits) {
+            println!("{}", mv);
+        }
+
+        return Ok(());
+    }
+
+    // Positions landed on are counted while solving, only if requested
+    let mut landings = args.flag("landings").then(Landings::new);
+    let (part1, part2) = match args.positional()[..] {
+        // Stream the input from a file given on the command line
+        [path] => solve_stream(
+            BufReader::new(File::open(path)?),
+            &dial,
+            thread_count,
+            landings.as_mut(),
+        )?,
+        _ => solve(&raw_data, &dial, thread_count, landings.as_mut())?,
+    };
 
     println!("Solver - day 01:");
 
     // Part 1
-    let mut cursor = Cursor::new(50, 100);
-    let result = solve_part1(&mut cursor, &all_moves);
-    println!("  Part 1 - Final code: {}", result);
+    println!("  Part 1 - Final code: {}", part1);
 
     // Part 2
-    let mut cursor = Cursor::new(50, 100);
-    let result = solve_part2(&mut cursor, &all_moves);
-    println!("  Part 2 - Final code: {}", result);
+    println!("  Part 2 - Final code: {}", part2);
+
+    // Positions landed on most
+    if let Some(landings) = landings {
+        let mut positions: Vec<(i32, usize)> = landings.into_iter().collect();
+        positions.sort_by_key(|(position, count)| (Reverse(*count), *position));
+
+        println!("  Most landed positions:");
+        for (position, count) in positions.iter().take(5) {
+            println!("    {}: {} times", position, count);
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
+    use shared::property;
 
     const RAW_INPUT: &str = "L68
 L30
@@ -118,17 +599,376 @@ L82";
 
     #[test]
     fn check_part1_result() {
-        let mut cursor = Cursor::new(50, 100);
-        let all_moves = parse_input(RAW_INPUT);
-        let result = solve_part1(&mut cursor, &all_moves);
+        let (result, _) = solve(RAW_INPUT, &Dial::default(), 1, None).unwrap();
         assert_eq!(result, 3);
     }
 
     #[test]
     fn check_part2_result() {
-        let mut cursor = Cursor::new(50, 100);
-        let all_moves = parse_input(RAW_INPUT);
-        let result = solve_part2(&mut cursor, &all_moves);
+        let (_, result) = solve(RAW_INPUT, &Dial::default(), 1, None).unwrap();
         assert_eq!(result, 6);
     }
+
+    #[test]
+    fn check_stream_result() {
+        let result = solve_stream(RAW_INPUT.as_bytes(), &Dial::default(), 1, None).unwrap();
+        assert_eq!(result, (3, 6));
+    }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 50);
+            let all_moves = parse_input(&raw_data, &Dial::default()).unwrap();
+            assert_eq!(all_moves.len(), 50);
+            assert!(
+                all_moves
+                    .iter()
+                    .all(|mv| matches!(mv, Move::Left(1..=999) | Move::Right(1..=999)))
+            );
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=20,
+            20,
+            |input| {
+                let all_moves = parse_input(input, &Dial::default()).unwrap();
+                count_codes_parallel(&mut Dial::default().cursor(), &all_moves, 3, None)
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 10);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, |input| {
+            solve(input, &Dial::default(), 1, None)
+        });
+
+        let error = solve("R1000000001", &Dial::default(), 1, None).unwrap_err();
+        assert_eq!(
+            error.message,
+            "integer `1000000001` out of range 0..=1000000000"
+        );
+    }
+
+    #[test]
+    fn check_move_grammar() {
+        let raw_data = "=0 # Reset the dial
+R10x5
+
+# Back to 5
+L5";
+        let dial = Dial { size: 10, start: 3 };
+        assert_eq!(solve(raw_data, &dial, 1, None).unwrap(), (6, 6));
+        assert_eq!(
+            solve_stream(raw_data.as_bytes(), &dial, 1, None).unwrap(),
+            (6, 6)
+        );
+
+        let error = solve("=10", &dial, 1, None).unwrap_err();
+        assert_eq!(error.message, "integer `10` out of range 0..=9");
+        assert!(solve("R10x", &dial, 1, None).is_err());
+    }
+
+    #[test]
+    fn check_dial_from_args() {
+        let dial = Dial::from_args(&Args::new(["--dial-size=10", "--start=3"])).unwrap();
+        assert_eq!((dial.size, dial.start), (10, 3));
+        assert!(Dial::from_args(&Args::new(["--dial-size=10", "--start=10"])).is_err());
+        assert!(Dial::from_args(&Args::new(["--dial-size=0"])).is_err());
+        let dial = Dial::from_args(&Args::new(["--dial-size=10"])).unwrap();
+        assert_eq!((dial.size, dial.start), (10, 5));
+        let dial = Dial::from_args(&Args::new(["--dial-size=1"])).unwrap();
+        assert_eq!((dial.size, dial.start), (1, 0));
+        let dial = Dial::from_args(&Args::new(Vec::<String>::new())).unwrap();
+        assert_eq!((dial.size, dial.start), (100, 50));
+    }
+
+    #[test]
+    fn check_events() {
+        let all_moves = parse_input(RAW_INPUT, &Dial::default()).unwrap();
+        let mut cursor = Dial::default().cursor();
+        let events: Vec<Event> = cursor.events(&all_moves[..3]).collect();
+        assert_eq!(
+            events,
+            [
+                Event::PassedZero(1),
+                Event::Wrapped(1),
+                Event::Position(82),
+                Event::Position(52),
+                Event::PassedZero(1),
+                Event::Wrapped(1),
+                Event::LandedOnZero,
+                Event::Position(0),
+            ]
+        );
+
+        // Leaving zero to the left wraps, but does not pass zero again
+        let mut cursor = Cursor::new(0, 10);
+        let events: Vec<Event> = cursor.events(&[Move::Left(25)]).collect();
+        assert_eq!(
+            events,
+            [Event::PassedZero(2), Event::Wrapped(3), Event::Position(5)]
+        );
+    }
+
+    #[test]
+    fn check_landing_histogram() {
+        let all_moves = parse_input(RAW_INPUT, &Dial::default()).unwrap();
+        let mut landings = Landings::new();
+        count_codes(
+            &mut Dial::default().cursor(),
+            &all_moves,
+            Some(&mut landings),
+        );
+        assert_eq!(landings.values().sum::<usize>(), all_moves.len());
+        assert_eq!(landings[&0], 3);
+        assert_eq!(landings.values().max(), Some(&3));
+
+        // Same positions when streaming the input, without a table for each position of the dial
+        let mut streamed = Landings::new();
+        let dial = Dial {
+            size: 1_000_000_000,
+            start: 50,
+        };
+        solve_stream(RAW_INPUT.as_bytes(), &dial, 1, Some(&mut streamed)).unwrap();
+        let mut solved = Landings::new();
+        solve(RAW_INPUT, &dial, 1, Some(&mut solved)).unwrap();
+        assert_eq!(streamed, solved);
+        assert_eq!(streamed.values().sum::<usize>(), all_moves.len());
+    }
+
+    #[test]
+    fn check_parallel() {
+        let mut rng = Rng::new(7);
+        for size in [1, 10, 37, 1000] {
+            let all_moves: Vec<Move> = (0..500)
+                .map(|_| match rng.between(0, 20) {
+                    0 => Move::Set(rng.between(0, size as usize - 1) as i32),
+                    1..=10 => Move::Left(rng.between(0, 4 * size as usize) as i32),
+                    _ => Move::Right(rng.between(0, 4 * size as usize) as i32),
+                })
+                .collect();
+
+            // Shifts compose associatively
+            let shifts: Vec<Shift> = all_moves.iter().map(|mv| Shift::of(mv, size)).collect();
+            for window in shifts.windows(3) {
+                let [a, b, c] = [window[0], window[1], window[2]];
+                assert_eq!(a.then(b, size).then(c, size), a.then(b.then(c, size), size));
+            }
+
+            for start in [0, size / 2, size - 1] {
+                let mut sequential = Cursor::new(start, size);
+                let mut expected_landings = Landings::new();
+                let expected =
+                    count_codes(&mut sequential, &all_moves, Some(&mut expected_landings));
+
+                // Chunks starting with rotations depend on their start position, until a `Set` move
+                for chunk_count in [1, 2, 3, 8, 100, 500] {
+                    let mut cursor = Cursor::new(start, size);
+                    let mut landings = Landings::new();
+                    let codes = count_codes_parallel(
+                        &mut cursor,
+                        &all_moves,
+                        chunk_count,
+                        Some(&mut landings),
+                    );
+                    assert_eq!(codes, expected);
+                    assert_eq!(cursor.target, sequential.target);
+                    assert_eq!(landings, expected_landings);
+                }
+            }
+        }
+    }
+
+    /// Turn the dial one click at a time, returns the final position, the number of clicks on zero
+    /// and the number of clicks between zero and the last position
+    fn simulate(size: i32, start: i32, steps: i32) -> (i32, i32, i32) {
+        let (mut position, mut zero_count, mut wrap_count) = (start, 0, 0);
+        for _ in 0..steps.abs() {
+            let next = modulo(position + steps.signum(), size);
+            if next == 0 {
+                zero_count += 1;
+            }
+            if (position == 0 && next == size - 1 && steps < 0)
+                || (position == size - 1 && next == 0 && steps > 0)
+            {
+                wrap_count += 1;
+            }
+            position = next;
+        }
+
+        (position, zero_count, wrap_count)
+    }
+
+    // Moves with negative steps turn left
+    fn to_move(steps: i32) -> Move {
+        if steps < 0 {
+            Move::Left(-steps)
+        } else {
+            Move::Right(steps)
+        }
+    }
+
+    #[test]
+    fn check_zero_count_property() {
+        // Dial size, start position (often zero) and steps of up to several turns
+        let generate = |rng: &mut Rng| {
+            let size = rng.between(1, 20);
+            let start = if rng.chance(0.3) {
+                0
+            } else {
+                rng.between(0, size - 1)
+            };
+            let steps = rng.between(0, 8 * size) as i32 * if rng.chance(0.5) { -1 } else { 1 };
+            (size as i32, start as i32, steps)
+        };
+
+        property::assert_property(5000, generate, |&(size, start, steps)| {
+            if size < 1 || !(0..size).contains(&start) {
+                return Ok(());
+            }
+
+            let mv = to_move(steps);
+            let mut cursor = Cursor::new(start, size);
+            let wrap_count = cursor.wrap_count(&mv);
+            let zero_count = cursor.mv_with_zero_count(&mv);
+
+            let expected = simulate(size, start, steps);
+            let actual = (cursor.target, zero_count, wrap_count);
+            if actual == expected {
+                Ok(())
+            } else {
+                Err(format!(
+                    "expected (position, zero count, wrap count) {:?}, found {:?}",
+                    expected, actual
+                ))
+            }
+        });
+    }
+
+    #[test]
+    fn check_move_sequence_property() {
+        let generate = |rng: &mut Rng| {
+            let size = rng.between(1, 10) as i32;
+            let moves: Vec<i32> = (0..rng.between(0, 20))
+                .map(|_| rng.between(0, 60) as i32 - 30)
+                .collect();
+            (size, moves)
+        };
+
+        property::assert_property(2000, generate, |(size, moves)| {
+            if *size < 1 {
+                return Ok(());
+            }
+
+            let start = 0;
+            let mut expected = (0, 0);
+            let mut position = start;
+            for steps in moves {
+                let (next, zero_count, _) = simulate(*size, position, *steps);
+                expected = (
+                    expected.0 + (next == 0) as usize,
+                    expected.1 + zero_count as usize,
+                );
+                position = next;
+            }
+
+            let all_moves: Vec<Move> = moves.iter().map(|steps| to_move(*steps)).collect();
+            let actual = count_codes(&mut Cursor::new(start, *size), &all_moves, None);
+            if actual == expected {
+                Ok(())
+            } else {
+                Err(format!("expected codes {:?}, found {:?}", expected, actual))
+            }
+        });
+    }
+
+    #[test]
+    fn check_inverse() {
+        let codes = |cursor: &Cursor, moves: &[Move]| {
+            count_codes(&mut Cursor::new(cursor.target, cursor.size), moves, None)
+        };
+
+        let all_moves = parse_input(RAW_INPUT, &Dial::default()).unwrap();
+        let cursor = Dial::default().cursor();
+        let edits = inverse::find_edits(&cursor, &all_moves, Rule::Part1, 3, 5).unwrap();
+        assert!(edits.is_empty());
+
+        let edits = inverse::find_edits(&cursor, &all_moves, Rule::Part2, 20, 5).unwrap();
+        assert_eq!(edits.len(), 1);
+        let edited = inverse::apply_edits(&all_moves, &edits);
+        assert_eq!(codes(&cursor, &edited).1, 20);
+
+        // Compare with all single edits on small dials: only codes they reach need one edit
+        let mut rng = Rng::new(3);
+        for _ in 0..100 {
+            let size = rng.between(1, 4) as i32;
+            let cursor = Cursor::new(rng.between(0, size as usize - 1) as i32, size);
+            let all_moves: Vec<Move> = (0..rng.between(0, 4))
+                .map(|_| match rng.between(0, 2) {
+                    0 => Move::Left(rng.between(0, 10) as i32),
+                    1 => Move::Right(rng.between(0, 10) as i32),
+                    _ => Move::Set(rng.between(0, size as usize - 1) as i32),
+                })
+                .collect();
+
+            let candidates: Vec<Move> = (0..4 * size)
+                .flat_map(|steps| [Move::Left(steps), Move::Right(steps)])
+                .collect();
+            let mut single_edits = vec![];
+            for index in 0..=all_moves.len() {
+                for mv in &candidates {
+                    single_edits.push(inverse::apply_edits(
+                        &all_moves,
+                        &[inverse::Edit::Insert(index, *mv)],
+                    ));
+                    if index < all_moves.len() {
+                        single_edits.push(inverse::apply_edits(
+                            &all_moves,
+                            &[inverse::Edit::Change(index, *mv)],
+                        ));
+                    }
+                }
+            }
+
+            let original = codes(&cursor, &all_moves);
+            for (rule, code) in [Rule::Part1, Rule::Part2]
+                .into_iter()
+                .flat_map(|rule| (0..=3).map(move |code| (rule, code)))
+            {
+                let code_of = |moves: &[Move]| {
+                    let (part1, part2) = codes(&cursor, moves);
+                    if rule == Rule::Part1 { part1 } else { part2 }
+                };
+                let expected_count = if code_of(&all_moves) == code {
+                    Some(0)
+                } else if single_edits.iter().any(|moves| code_of(moves) == code) {
+                    Some(1)
+                } else {
+                    None
+                };
+
+                let edits = inverse::find_edits(&cursor, &all_moves, rule, code, 1);
+                assert_eq!(edits.as_ref().map(|edits| edits.len()), expected_count);
+                if let Some(edits) = edits {
+                    assert_eq!(code_of(&inverse::apply_edits(&all_moves, &edits)), code);
+                }
+            }
+
+            // Enough edits reach any code
+            let code = original.0 + 2;
+            let edits = inverse::find_edits(&cursor, &all_moves, Rule::Part1, code, 10).unwrap();
+            assert_eq!(
+                codes(&cursor, &inverse::apply_edits(&all_moves, &edits)).0,
+                code
+            );
+        }
+    }
 }
@@ -0,0 +1,23 @@
+/// Brute force solver turning the dial one click at a time
+pub fn solve(raw_data: &str) -> (usize, usize) {
+    let mut position = 50;
+    let mut landed_on_zero = 0;
+    let mut passed_zero = 0;
+
+    for line in raw_data.lines() {
+        let (direction, steps) = line.split_at(1);
+        let step = if direction == "L" { -1 } else { 1 };
+        for _ in 0..steps.parse::<i32>().unwrap() {
+            position = (position + step + 100) % 100;
+            if position == 0 {
+                passed_zero += 1;
+            }
+        }
+
+        if position == 0 {
+            landed_on_zero += 1;
+        }
+    }
+
+    (landed_on_zero, passed_zero)
+}
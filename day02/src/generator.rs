@@ -0,0 +1,15 @@
+use shared::Rng;
+
+/// Generate `size` ID ranges of up to 10 digits, on a single line
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let ranges: Vec<String> = (0..size)
+        .map(|_| {
+            let digit_count = rng.between(1, 10) as u32;
+            let min = rng.between(1, 10_usize.pow(digit_count) - 1);
+            let max = min + rng.between(0, 100_000);
+            format!("{}-{}", min, max)
+        })
+        .collect();
+
+    ranges.join(",") + "\n"
+}
@@ -1,109 +1,656 @@
-use std::collections::HashSet;
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::{Args, ArgsError};
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::number::{Number, OverflowError};
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use shared::trace;
+use shared::{IntervalSet, Range};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+// IDs are written with digits and letters
+const MIN_BASE: u32 = 2;
+const MAX_BASE: u32 = 36;
+
+// Fuzzed ranges containing more repeated IDs are only parsed
+const MAX_FUZZ_ID_COUNT: u128 = 100_000;
 
-struct Range {
-    min: usize,
-    max: usize,
+/// What to do with ranges written `max-min`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ReversedRanges {
+    Reject,
+    Swap,
+}
+
+impl FromStr for ReversedRanges {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "reject" => Ok(ReversedRanges::Reject),
+            "swap" => Ok(ReversedRanges::Swap),
+            _ => Err(format!("unknown policy `{}`", value)),
+        }
+    }
 }
 
-fn parse_input(raw_data: &str) -> Vec<Range> {
-    raw_data
-        .split(",")
-        .map(|part| {
-            let bounds: Vec<&str> = part.split("-").collect();
-            Range {
-                min: bounds[0].parse::<usize>().unwrap(),
-                max: bounds[1].parse::<usize>().unwrap(),
+fn parse_input(
+    raw_data: &str,
+    base: u32,
+    reversed: ReversedRanges,
+) -> Result<Vec<Range>, ParseError> {
+    let mut parser = Parser::new(raw_data);
+    let ranges = parser.separated(",", |p| {
+        let start = *p;
+        let range = p.range_radix(base)?;
+        match reversed {
+            _ if range.min <= range.max => Ok(range),
+            ReversedRanges::Reject => {
+                Err(start.error("reversed range, its minimum is after its maximum"))
             }
-        })
-        .collect()
+            ReversedRanges::Swap => Ok(Range::new(range.max, range.min)),
+        }
+    })?;
+    parser.skip_whitespace();
+    parser.end()?;
+
+    Ok(ranges)
 }
 
-fn solve_part1(ranges: &Vec<Range>) -> usize {
-    let mut invalid_ids = HashSet::new();
+/// Pairs of input ranges sharing IDs, by index
+fn overlapping_ranges(ranges: &[Range]) -> Vec<(usize, usize)> {
+    let mut sorted: Vec<usize> = (0..ranges.len()).collect();
+    sorted.sort_by_key(|index| ranges[*index].min);
 
-    for range in ranges {
-        for value in range.min..=range.max {
-            let str_value = value.to_string();
-            if str_value.len() % 2 != 0 {
-                continue;
+    // Ranges starting before the end of a range overlap it
+    let mut overlaps = vec![];
+    for (position, index) in sorted.iter().enumerate() {
+        for other in &sorted[position + 1..] {
+            if ranges[*other].min > ranges[*index].max {
+                break;
             }
 
-            let half_len = str_value.len() / 2;
-            let first_half = &str_value[0..half_len];
-            let second_half = &str_value[half_len..];
-            if first_half == second_half {
-                invalid_ids.insert(value);
-            }
+            overlaps.push((*index.min(other), *index.max(other)));
+        }
+    }
+
+    overlaps.sort();
+    overlaps
+}
+
+/// Write an ID in a base, with lowercase letters
+fn format_id(mut id: usize, base: u32) -> String {
+    let mut digits = vec![];
+    loop {
+        digits.push(char::from_digit((id % base as usize) as u32, base).unwrap());
+        id /= base as usize;
+        if id == 0 {
+            return digits.iter().rev().collect();
         }
     }
+}
+
+/// Number of digits of the largest ID in a base
+fn max_digits(base: u32) -> u32 {
+    usize::MAX.ilog(base as usize) + 1
+}
+
+/// Multiplier repeating a block of `block_len` digits to `len` digits: `(base^len − 1) /
+/// (base^block_len − 1)`, e.g. 1001 to repeat a block of 3 decimal digits twice
+fn multiplier(base: u32, len: u32, block_len: u32) -> u128 {
+    let base = base as u128;
+    (base.pow(len) - 1) / (base.pow(block_len) - 1)
+}
+
+/// Blocks of `block_len` digits which, repeated to `len` digits, give an ID of a range (IDs being
+/// `block × multiplier`), and their multiplier
+fn repeated_blocks(
+    range: &Range,
+    base: u32,
+    len: u32,
+    block_len: u32,
+) -> (RangeInclusive<u128>, u128) {
+    let multiplier = multiplier(base, len, block_len);
+    let block_base = base as u128;
+    let min_block = block_base
+        .pow(block_len - 1)
+        .max((range.min as u128).div_ceil(multiplier));
+    let max_block = (block_base.pow(block_len) - 1).min(range.max as u128 / multiplier);
+    (min_block..=max_block, multiplier)
+}
 
-    invalid_ids.iter().sum()
+/// IDs of a range made of a block of `block_len` digits repeated to `len` digits, in increasing
+/// order
+fn repeated_ids(range: &Range, base: u32, len: u32, block_len: u32) -> impl Iterator<Item = usize> {
+    let (blocks, multiplier) = repeated_blocks(range, base, len, block_len);
+    blocks.map(move |block| (block * multiplier) as usize)
 }
 
-fn solve_part2(ranges: &Vec<Range>) -> usize {
-    let mut invalid_ids = HashSet::new();
+/// Number of times a block can be repeated to `len` digits
+fn repeat_counts(len: u32) -> impl Iterator<Item = u32> {
+    (2..=len).filter(move |repeat_count| len.is_multiple_of(*repeat_count))
+}
 
+/// Upper bound of the number of invalid IDs of any query, without enumerating them
+fn repeated_id_count(ranges: &[Range], base: u32) -> u128 {
+    let mut count = 0;
     for range in ranges {
-        for value in range.min..=range.max {
-            let str_value = value.to_string();
+        for len in 2..=max_digits(base) {
+            for repeat_count in repeat_counts(len) {
+                let (blocks, _) = repeated_blocks(range, base, len, len / repeat_count);
+                count += (blocks.end() + 1).saturating_sub(*blocks.start());
+            }
+        }
+    }
 
-            for chunk_size in 1..=str_value.len() / 2 {
-                if str_value.len() % chunk_size != 0 {
-                    continue;
-                }
+    count
+}
 
-                let chunks_count = str_value.len() / chunk_size;
-                let first_chunk = &str_value[0..chunk_size];
-                for i in 1..chunks_count {
-                    let start = i * chunk_size;
-                    let end = start + chunk_size;
-                    if &str_value[start..end] != first_chunk {
-                        break;
+/// Repeat counts selected by a query
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum RepeatCount {
+    Exactly(u32),
+    AtLeast(u32),
+    In(Vec<u32>),
+}
+
+impl RepeatCount {
+    fn matches(&self, repeat_count: u32) -> bool {
+        match self {
+            RepeatCount::Exactly(count) => repeat_count == *count,
+            RepeatCount::AtLeast(count) => repeat_count >= *count,
+            RepeatCount::In(counts) => counts.contains(&repeat_count),
+        }
+    }
+}
+
+impl FromStr for RepeatCount {
+    type Err = String;
+
+    /// `k` for exactly k repeats, `k..` for at least k, `k,l,...` for one of them
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let parse_count = |count: &str| {
+            count
+                .parse()
+                .map_err(|_| format!("invalid repeat count `{}`", value))
+        };
+
+        if let Some(count) = value.strip_suffix("..") {
+            Ok(RepeatCount::AtLeast(parse_count(count)?))
+        } else if value.contains(',') {
+            let counts = value
+                .split(',')
+                .map(parse_count)
+                .collect::<Result<_, _>>()?;
+            Ok(RepeatCount::In(counts))
+        } else {
+            Ok(RepeatCount::Exactly(parse_count(value)?))
+        }
+    }
+}
+
+/// Selection of invalid IDs written in a base, by the number of times their block is repeated
+/// and the length of this block. The block of an ID is its longest one: 1111 is 11 repeated
+/// twice, not 1 repeated four times.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Query {
+    /// Puzzle part the query answers, reported in traces
+    part: Option<usize>,
+    base: u32,
+    repeat_count: RepeatCount,
+    block_lens: RangeInclusive<u32>,
+}
+
+impl Query {
+    /// IDs made of a block repeated twice
+    fn part1(base: u32) -> Query {
+        Query {
+            part: Some(1),
+            base,
+            repeat_count: RepeatCount::Exactly(2),
+            block_lens: 1..=max_digits(base),
+        }
+    }
+
+    /// IDs made of a block repeated at least twice
+    fn part2(base: u32) -> Query {
+        Query {
+            part: Some(2),
+            base,
+            repeat_count: RepeatCount::AtLeast(2),
+            block_lens: 1..=max_digits(base),
+        }
+    }
+
+    /// Trace a selected ID, with the part it counts for if any
+    fn trace(&self, id: usize, repeat_count: u32, block_len: u32) {
+        let mut fields: Vec<(&'static str, &dyn fmt::Display)> = vec![];
+        if let Some(part) = &self.part {
+            fields.push(("part", part));
+        }
+        fields.extend([
+            ("id", &id as &dyn fmt::Display),
+            ("repeat_count", &repeat_count),
+            ("block_len", &block_len),
+        ]);
+        trace::event("invalid_id", &fields);
+    }
+
+    /// Selected IDs of ranges, sorted and without duplicates
+    fn list(&self, ranges: &[Range]) -> Vec<usize> {
+        let mut invalid_ids = vec![];
+
+        // Once overlapping ranges are merged, each ID is found once: in a single range and for a
+        // single repeat count
+        for range in IntervalSet::new(ranges.iter().copied()).iter() {
+            for len in 2..=max_digits(self.base) {
+                for repeat_count in
+                    repeat_counts(len).filter(|count| self.repeat_count.matches(*count))
+                {
+                    let block_len = len / repeat_count;
+                    if !self.block_lens.contains(&block_len) {
+                        continue;
                     }
 
-                    if i == chunks_count - 1 {
-                        invalid_ids.insert(value);
+                    // Skip IDs which are also made of fewer repeats of a longer block
+                    let longer_blocks: Vec<u128> = repeat_counts(len)
+                        .take_while(|count| *count < repeat_count)
+                        .map(|count| multiplier(self.base, len, len / count))
+                        .collect();
+                    for value in repeated_ids(range, self.base, len, block_len) {
+                        if longer_blocks
+                            .iter()
+                            .all(|multiplier| !(value as u128).is_multiple_of(*multiplier))
+                        {
+                            invalid_ids.push(value);
+                            self.trace(value, repeat_count, block_len);
+                        }
                     }
                 }
             }
         }
+
+        invalid_ids.sort();
+        invalid_ids
     }
+}
+
+fn sum_ids(invalid_ids: &[usize]) -> Result<usize, OverflowError> {
+    invalid_ids
+        .iter()
+        .try_fold(0, |sum: usize, id| sum.try_add(id))
+}
 
-    invalid_ids.iter().sum()
+fn solve_part1(ranges: &[Range], base: u32) -> Result<usize, OverflowError> {
+    sum_ids(&Query::part1(base).list(ranges))
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-02.txt");
-    let all_ranges = parse_input(raw_data);
+fn solve_part2(ranges: &[Range], base: u32) -> Result<usize, OverflowError> {
+    sum_ids(&Query::part2(base).list(ranges))
+}
+
+fn fuzz_target(raw_data: &str, base: u32, reversed: ReversedRanges) -> Result<(), Box<dyn Error>> {
+    let all_ranges = parse_input(raw_data, base, reversed)?;
+    if repeated_id_count(&all_ranges, base) <= MAX_FUZZ_ID_COUNT {
+        solve_part1(&all_ranges, base)?;
+        solve_part2(&all_ranges, base)?;
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    trace::enable_from_args(&args);
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-02.txt"),
+        TrailingWhitespace::Trim,
+    );
+    let base = args.value_or("base", 10)?;
+    if !(MIN_BASE..=MAX_BASE).contains(&base) {
+        return Err(ArgsError {
+            message: format!("base must be between {} and {}", MIN_BASE, MAX_BASE),
+        }
+        .into());
+    }
+
+    let reversed = args.value_or("reversed-ranges", ReversedRanges::Reject)?;
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, |input| {
+        fuzz_target(input, base, reversed)
+    })? {
+        return Ok(());
+    }
+
+    // Ranges written in another base are read from a file given on the command line, the embedded
+    // input being decimal
+    let raw_data = match args.positional()[..] {
+        [path] => fs::read_to_string(path)?,
+        _ if base != 10 => {
+            return Err(ArgsError {
+                message: "a base other than 10 requires an input file".to_string(),
+            }
+            .into());
+        }
+        _ => raw_data,
+    };
+    let all_ranges = parse_input(&raw_data, base, reversed)?;
 
     println!("Solver - day 02:");
 
     // Part 1
-    let result = solve_part1(&all_ranges);
+    let result = solve_part1(&all_ranges, base)?;
     println!("  Part 1 - Final code: {}", result);
 
     // Part 2
-    let result = solve_part2(&all_ranges);
+    let result = solve_part2(&all_ranges, base)?;
     println!("  Part 2 - Final code: {}", result);
+
+    // Other selections of IDs, given by `--repeats`, `--min-block-len` and `--max-block-len`
+    if let Some(repeat_count) = args.value("repeats")? {
+        let min_block_len = args.value_or("min-block-len", 1)?;
+        let max_block_len = args.value_or("max-block-len", max_digits(base))?;
+        let query = Query {
+            part: None,
+            base,
+            repeat_count,
+            block_lens: min_block_len..=max_block_len,
+        };
+        let invalid_ids = query.list(&all_ranges);
+        println!(
+            "  Query - Count: {}, sum: {}",
+            invalid_ids.len(),
+            sum_ids(&invalid_ids)?
+        );
+    }
+
+    // Input ranges sharing IDs, which are only counted once
+    if args.flag("overlaps") {
+        let overlaps = overlapping_ranges(&all_ranges);
+        println!("  Overlapping ranges: {}", overlaps.len());
+        for (index1, index2) in overlaps {
+            let [range1, range2] = [all_ranges[index1], all_ranges[index2]].map(|range| {
+                format!(
+                    "{}-{}",
+                    format_id(range.min, base),
+                    format_id(range.max, base)
+                )
+            });
+            println!("    #{} {} and #{} {}", index1, range1, index2, range2);
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
 
     #[test]
     fn check_part1_result() {
-        let all_ranges = parse_input(RAW_INPUT);
-        let result = solve_part1(&all_ranges);
+        let all_ranges = parse_input(RAW_INPUT, 10, ReversedRanges::Reject).unwrap();
+        let result = solve_part1(&all_ranges, 10).unwrap();
         assert_eq!(result, 1227775554);
     }
 
     #[test]
     fn check_part2_result() {
-        let all_ranges = parse_input(RAW_INPUT);
-        let result = solve_part2(&all_ranges);
+        let all_ranges = parse_input(RAW_INPUT, 10, ReversedRanges::Reject).unwrap();
+        let result = solve_part2(&all_ranges, 10).unwrap();
         assert_eq!(result, 4174379265);
     }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..5 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 5);
+            let all_ranges = parse_input(&raw_data, 10, ReversedRanges::Reject).unwrap();
+            assert_eq!(all_ranges.len(), 5);
+            for range in all_ranges {
+                // Up to 10 digits, spanning at most 100000 IDs
+                assert!((1..10_000_000_000).contains(&range.min));
+                assert!(range.max - range.min <= 100_000);
+            }
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=3,
+            10,
+            |input| {
+                let all_ranges = parse_input(input, 10, ReversedRanges::Reject).unwrap();
+                (
+                    solve_part1(&all_ranges, 10).unwrap(),
+                    solve_part2(&all_ranges, 10).unwrap(),
+                )
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_wide_range() {
+        let all_ranges = parse_input("1-99999", 10, ReversedRanges::Reject).unwrap();
+        assert_eq!(
+            (
+                solve_part1(&all_ranges, 10).unwrap(),
+                solve_part2(&all_ranges, 10).unwrap()
+            ),
+            reference::solve("1-99999")
+        );
+
+        // Ten billions of IDs, but only 90000 of them repeat a block of 5 digits twice
+        let all_ranges = parse_input("1000000000-9999999999", 10, ReversedRanges::Reject).unwrap();
+        let block_sum: usize = (10000..=99999).sum();
+        assert_eq!(solve_part1(&all_ranges, 10).unwrap(), 100001 * block_sum);
+        assert_eq!(repeated_id_count(&all_ranges, 10), 90000 + 90 + 9);
+    }
+
+    #[test]
+    fn check_query() {
+        // Smallest number of repeats of a block giving an ID, computed digit by digit
+        let repeat_count = |id: usize| {
+            let digits = id.to_string();
+            let len = digits.len();
+            (1..len)
+                .rev()
+                .filter(|block_len| len.is_multiple_of(*block_len))
+                .find(|block_len| digits == digits[..*block_len].repeat(len / block_len))
+                .map(|block_len| (len / block_len) as u32)
+        };
+
+        let all_ranges = parse_input("1-9999", 10, ReversedRanges::Reject).unwrap();
+        let queries = [
+            ("2", 1..=4),
+            ("3", 1..=4),
+            ("4", 1..=4),
+            ("2..", 1..=1),
+            ("3..", 1..=4),
+            ("2,4", 2..=4),
+        ];
+        for (repeat_count_query, block_lens) in queries {
+            let query = Query {
+                part: None,
+                base: 10,
+                repeat_count: repeat_count_query.parse().unwrap(),
+                block_lens: block_lens.clone(),
+            };
+            let expected: Vec<usize> = (1..=9999)
+                .filter(|id| {
+                    repeat_count(*id).is_some_and(|count| {
+                        let block_len = id.to_string().len() as u32 / count;
+                        query.repeat_count.matches(count) && block_lens.contains(&block_len)
+                    })
+                })
+                .collect();
+            assert_eq!(query.list(&all_ranges), expected);
+        }
+
+        // 1111 is made of 11 repeated twice, and of no block repeated exactly four times
+        let query = Query {
+            part: None,
+            base: 10,
+            repeat_count: RepeatCount::Exactly(4),
+            block_lens: 1..=max_digits(10),
+        };
+        assert!(query.list(&all_ranges).is_empty());
+        assert_eq!(
+            Query::part1(10)
+                .list(&parse_input("1111-1111", 10, ReversedRanges::Reject).unwrap())
+                .len(),
+            1
+        );
+
+        assert_eq!("3..".parse(), Ok(RepeatCount::AtLeast(3)));
+        assert_eq!("2,3".parse(), Ok(RepeatCount::In(vec![2, 3])));
+        assert!("2..3".parse::<RepeatCount>().is_err());
+    }
+
+    #[test]
+    fn check_bases() {
+        let all_ranges = parse_input("a-FF", 16, ReversedRanges::Reject).unwrap();
+        assert_eq!(all_ranges, [Range::new(10, 255)]);
+        // 0x11, 0x22, ..., 0xff
+        assert_eq!(
+            solve_part1(&all_ranges, 16).unwrap(),
+            17 * (1..=15).sum::<usize>()
+        );
+        assert!(parse_input("12-19", 8, ReversedRanges::Reject).is_err());
+
+        // Compare with digits computed one by one
+        let digits = |mut id: usize, base: usize| {
+            let mut digits = vec![];
+            while id > 0 {
+                digits.push(id % base);
+                id /= base;
+            }
+            digits
+        };
+        let is_repeated = |digits: &[usize], repeat_count: usize| {
+            let block_len = digits.len() / repeat_count;
+            digits.len().is_multiple_of(repeat_count)
+                && digits
+                    .chunks(block_len)
+                    .all(|block| block == &digits[..block_len])
+        };
+
+        for base in [2, 3, 7, 16, 36] {
+            let all_ranges = [Range::new(1, 3000)];
+            let (mut part1, mut part2) = (0, 0);
+            for id in 1..=3000 {
+                let digits = digits(id, base);
+                if is_repeated(&digits, 2) {
+                    part1 += id;
+                }
+                if (2..=digits.len()).any(|repeat_count| is_repeated(&digits, repeat_count)) {
+                    part2 += id;
+                }
+            }
+
+            let base = base as u32;
+            assert_eq!(solve_part1(&all_ranges, base), Ok(part1));
+            assert_eq!(solve_part2(&all_ranges, base), Ok(part2));
+        }
+
+        fuzz::assert_no_crash(&["1z-zz,0-11111"], 500, |input| {
+            fuzz_target(input, 36, ReversedRanges::Reject)
+        });
+        let error =
+            parse_input(&format!("0-1{}", "0".repeat(64)), 2, ReversedRanges::Reject).unwrap_err();
+        assert_eq!(
+            error.message,
+            format!("integer `1{}` out of range", "0".repeat(64))
+        );
+    }
+
+    #[test]
+    fn check_overlaps() {
+        let raw_data = "10-30,25-40,50-60,35-36,66-55";
+        let error = parse_input(raw_data, 10, ReversedRanges::Reject).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 25));
+
+        let all_ranges = parse_input(raw_data, 10, ReversedRanges::Swap).unwrap();
+        assert_eq!(all_ranges[4], Range::new(55, 66));
+        assert_eq!(overlapping_ranges(&all_ranges), [(0, 1), (1, 3), (2, 4)]);
+
+        // IDs shared by ranges are counted once
+        let ids = [11, 22, 33, 55, 66];
+        assert_eq!(Query::part1(10).list(&all_ranges), ids);
+        assert_eq!(solve_part2(&all_ranges, 10), Ok(ids.iter().sum::<usize>()));
+
+        assert_eq!(format_id(255, 16), "ff");
+        assert_eq!(format_id(0, 2), "0");
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 3);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, |input| {
+            fuzz_target(input, 10, ReversedRanges::Reject)
+        });
+
+        let all_ranges = parse_input(
+            "11111111111111111111-11111111111111111111,12121212121212121212-12121212121212121212",
+            10,
+            ReversedRanges::Reject,
+        )
+        .unwrap();
+        assert!(solve_part1(&all_ranges, 10).is_err());
+        assert!(solve_part2(&all_ranges, 10).is_err());
+    }
+
+    #[test]
+    fn check_trace() {
+        let all_ranges = parse_input(RAW_INPUT, 10, ReversedRanges::Reject).unwrap();
+        let (_, events) = trace::capture(|| solve_part1(&all_ranges, 10));
+        let mut ids: Vec<&str> = events
+            .iter()
+            .filter_map(|event| event.field("id"))
+            .collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            [
+                "1010",
+                "11",
+                "1188511885",
+                "22",
+                "222222",
+                "38593859",
+                "446446",
+                "99"
+            ]
+        );
+        assert!(events.iter().all(|event| event.field("part") == Some("1")));
+
+        // Other queries do not count for a part
+        let query = Query {
+            part: None,
+            base: 10,
+            repeat_count: RepeatCount::AtLeast(3),
+            block_lens: 1..=max_digits(10),
+        };
+        let (_, events) = trace::capture(|| query.list(&all_ranges));
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|event| event.field("part").is_none()));
+    }
 }
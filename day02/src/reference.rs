@@ -0,0 +1,31 @@
+/// Check if an ID is made of a sequence of digits repeated `repeat_count` times
+fn is_repeated(id: &str, repeat_count: usize) -> bool {
+    id.len().is_multiple_of(repeat_count)
+        && id[..id.len() / repeat_count].repeat(repeat_count) == id
+}
+
+/// Brute force solver checking every ID of every range
+pub fn solve(raw_data: &str) -> (usize, usize) {
+    let mut ids = vec![];
+    for range in raw_data.trim().split(',') {
+        let (min, max) = range.split_once('-').unwrap();
+        ids.extend(min.parse::<usize>().unwrap()..=max.parse().unwrap());
+    }
+    ids.sort();
+    ids.dedup();
+
+    let mut part1 = 0;
+    let mut part2 = 0;
+    for id in ids {
+        let digits = id.to_string();
+        if is_repeated(&digits, 2) {
+            part1 += id;
+        }
+
+        if (2..=digits.len()).any(|repeat_count| is_repeated(&digits, repeat_count)) {
+            part2 += id;
+        }
+    }
+
+    (part1, part2)
+}
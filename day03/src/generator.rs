@@ -0,0 +1,16 @@
+use shared::Rng;
+
+const BANK_LEN: usize = 100;
+
+/// Generate `size` banks of 100 batteries (joltages from 1 to 9)
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+    for _ in 0..size {
+        for _ in 0..BANK_LEN {
+            output.push_str(&rng.between(1, 9).to_string());
+        }
+        output.push('\n');
+    }
+
+    output
+}
@@ -1,79 +1,144 @@
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::number::{self, Number, OverflowError, Precision, Solve};
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use shared::trace;
+use std::error::Error;
+
 type Bank = Vec<usize>;
 
-fn parse_input(raw_data: &str) -> Vec<Bank> {
-    raw_data
-        .lines()
-        .map(|line| {
-            let bank: Bank = line
-                .chars()
-                .map(|c| c.to_digit(10).unwrap() as usize)
-                .collect();
+// Number of batteries turned on in each bank for part 2
+const PART2_DIGIT_COUNT: usize = 12;
 
-            bank
-        })
-        .collect()
+fn parse_bank(line: &mut Parser) -> Result<Bank, ParseError> {
+    line.cells(|c| c.to_digit(10).map(|digit| digit as usize))
 }
 
-/// Recursive function used to add the maximum joltage from a bank of batteries
-fn add_max_joltage_rec(acc_max_joltage: &mut usize, batteries: &[usize], digit_count: usize) {
-    if digit_count == 0 {
-        return;
-    }
+fn parse_input(raw_data: &str) -> Result<Vec<Bank>, ParseError> {
+    Parser::new(raw_data)
+        .lines()
+        .map(|mut line| parse_bank(&mut line))
+        .collect()
+}
 
-    let mut max_joltage = 0;
-    let mut max_index = 0;
-    for i in 0..batteries.len() - digit_count + 1 {
-        if batteries[i] > max_joltage {
-            max_joltage = batteries[i];
-            max_index = i;
+/// Largest subsequence of `digit_count` batteries (or all of them if there are fewer), in O(n):
+/// each battery replaces the smaller ones chosen before it, as long as enough batteries remain
+/// to complete the selection
+fn max_joltage_digits(batteries: &[usize], digit_count: usize) -> Vec<usize> {
+    let mut removable_count = batteries.len().saturating_sub(digit_count);
+    let mut digits = Vec::with_capacity(batteries.len());
+    for battery in batteries {
+        while removable_count > 0 && digits.last().is_some_and(|last| last < battery) {
+            digits.pop();
+            removable_count -= 1;
         }
+
+        digits.push(*battery);
     }
 
-    *acc_max_joltage += max_joltage * 10_usize.pow((digit_count - 1) as u32);
+    digits.truncate(digit_count);
+    digits
+}
 
-    add_max_joltage_rec(
-        acc_max_joltage,
-        &batteries[(max_index + 1)..],
-        digit_count - 1,
-    );
+/// Maximum joltage of a bank of batteries, using `digit_count` of them
+fn max_joltage<T: Number>(batteries: &[usize], digit_count: usize) -> Result<T, OverflowError> {
+    let ten = T::from_usize(10);
+    max_joltage_digits(batteries, digit_count)
+        .iter()
+        .try_fold(T::zero(), |joltage, digit| {
+            joltage.try_mul(&ten)?.try_add(&T::from_usize(*digit))
+        })
 }
 
-fn solve_part1(banks: &Vec<Bank>) -> usize {
-    let mut code = 0;
-    for bank in banks {
-        add_max_joltage_rec(&mut code, &bank[..], 2);
+/// Sum of the maximum joltages of all banks, using `digit_count` batteries per bank
+fn max_joltage_sum<T: Number>(banks: &[Bank], digit_count: usize) -> Result<T, OverflowError> {
+    let mut max_joltage_sum = T::zero();
+    for (bank_index, bank) in banks.iter().enumerate() {
+        let max_joltage: T = max_joltage(bank, digit_count)?;
+        trace::event(
+            "chosen_digits",
+            &[("bank", &(bank_index + 1)), ("digits", &max_joltage)],
+        );
+        max_joltage_sum = max_joltage_sum.try_add(&max_joltage)?;
     }
 
-    code
+    Ok(max_joltage_sum)
 }
 
-fn solve_part2(banks: &Vec<Bank>) -> usize {
-    let mut max_joltage_sum = 0;
-    for bank in banks {
-        add_max_joltage_rec(&mut max_joltage_sum, &bank[..], 12);
+fn solve_part1<T: Number>(banks: &[Bank]) -> Result<T, OverflowError> {
+    max_joltage_sum(banks, 2)
+}
+
+fn solve_part2<T: Number>(banks: &[Bank]) -> Result<T, OverflowError> {
+    max_joltage_sum(banks, PART2_DIGIT_COUNT)
+}
+
+// Adapters used to select the integer type of the results at runtime
+struct Part1<'a>(&'a [Bank]);
+struct Part2<'a>(&'a [Bank]);
+
+impl Solve for Part1<'_> {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+        solve_part1(self.0)
     }
+}
 
-    max_joltage_sum
+impl Solve for Part2<'_> {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+        solve_part2(self.0)
+    }
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-03.txt");
-    let all_banks = parse_input(raw_data);
+fn fuzz_target(raw_data: &str) -> Result<(), Box<dyn Error>> {
+    let all_banks = parse_input(raw_data)?;
+    solve_part1::<usize>(&all_banks)?;
+    solve_part2::<usize>(&all_banks)?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    trace::enable_from_args(&args);
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let precision = args.value_or("precision", Precision::Checked)?;
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-03.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let all_banks = parse_input(&raw_data)?;
 
     println!("Solver - day 03:");
 
     // Part 1
-    let result = solve_part1(&all_banks);
+    let result = number::solve_with(&Part1(&all_banks), precision)?;
     println!("  Part 1 - Final code: {}", result);
 
     // Part 2
-    let result = solve_part2(&all_banks);
+    let result = number::solve_with(&Part2(&all_banks), precision)?;
     println!("  Part 2 - Final code: {}", result);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "987654321111111
 811111111111119
@@ -82,15 +147,116 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let all_banks = parse_input(RAW_INPUT);
-        let result = solve_part1(&all_banks);
+        let all_banks = parse_input(RAW_INPUT).unwrap();
+        let result = solve_part1::<usize>(&all_banks).unwrap();
         assert_eq!(result, 357);
     }
 
     #[test]
     fn check_part2_result() {
-        let all_banks = parse_input(RAW_INPUT);
-        let result = solve_part2(&all_banks);
+        let all_banks = parse_input(RAW_INPUT).unwrap();
+        let result = solve_part2::<usize>(&all_banks).unwrap();
         assert_eq!(result, 3121910778619);
     }
+
+    #[test]
+    fn check_overflow() {
+        let all_banks = parse_input("9876543210987654321098765").unwrap();
+        assert!(max_joltage_sum::<usize>(&all_banks, 25).is_err());
+        assert_eq!(
+            max_joltage_sum::<u128>(&all_banks, 25),
+            Ok(9876543210987654321098765)
+        );
+    }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 20);
+            let all_banks = parse_input(&raw_data).unwrap();
+            assert_eq!(all_banks.len(), 20);
+            for bank in &all_banks {
+                assert_eq!(bank.len(), 100);
+                assert!(bank.iter().all(|battery| (1..=9).contains(battery)));
+            }
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=5,
+            20,
+            |input| {
+                let all_banks = parse_input(input).unwrap();
+                (
+                    solve_part1::<u128>(&all_banks).unwrap(),
+                    solve_part2::<u128>(&all_banks).unwrap(),
+                )
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 3);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, fuzz_target);
+
+        // Banks too short for part 2 turn all their batteries on
+        let all_banks = parse_input("12345678901\n").unwrap();
+        assert_eq!(solve_part1::<usize>(&all_banks), Ok(91));
+        assert_eq!(solve_part2::<usize>(&all_banks), Ok(12345678901));
+    }
+
+    #[test]
+    fn check_trace() {
+        let all_banks = parse_input(RAW_INPUT).unwrap();
+        let (_, events) = trace::capture(|| solve_part1::<usize>(&all_banks));
+        let digits: Vec<&str> = events
+            .iter()
+            .filter_map(|event| event.field("digits"))
+            .collect();
+        assert_eq!(digits, ["98", "89", "78", "92"]);
+    }
+
+    #[test]
+    fn check_digit_selection() {
+        // Scan the remaining batteries for each digit, keeping the first largest one
+        let greedy = |batteries: &[usize], digit_count: usize| {
+            let mut digits = vec![];
+            let mut start = 0;
+            for remaining in (1..=digit_count).rev() {
+                let candidates = &batteries[start..=batteries.len() - remaining];
+                let max = *candidates.iter().max().unwrap();
+                start += candidates
+                    .iter()
+                    .position(|battery| *battery == max)
+                    .unwrap()
+                    + 1;
+                digits.push(max);
+            }
+            digits
+        };
+
+        let mut rng = Rng::new(5);
+        for _ in 0..200 {
+            let bank: Bank = (0..rng.between(1, 30)).map(|_| rng.between(1, 9)).collect();
+            for digit_count in 1..=bank.len() {
+                assert_eq!(
+                    max_joltage_digits(&bank, digit_count),
+                    greedy(&bank, digit_count)
+                );
+            }
+            assert_eq!(max_joltage_digits(&bank, bank.len() + 1), bank);
+        }
+
+        // Large banks and digit counts
+        let bank: Bank = (0..50_000).map(|_| rng.between(1, 9)).collect();
+        let digits = max_joltage_digits(&bank, 25_000);
+        assert_eq!(digits.len(), 25_000);
+        assert!(digits.starts_with(&[9; 100]));
+        assert_eq!(max_joltage_digits(&bank, bank.len()), bank);
+    }
 }
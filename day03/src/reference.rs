@@ -0,0 +1,33 @@
+/// Largest joltage made of `digit_count` batteries of a bank, computed by dynamic programming over
+/// all the ways to pick them: `best[k]` is the largest joltage made of `k` batteries among the
+/// batteries seen so far
+fn max_joltage(bank: &[u128], digit_count: usize) -> u128 {
+    let mut best: Vec<Option<u128>> = vec![None; digit_count + 1];
+    best[0] = Some(0);
+    for battery in bank {
+        for k in (1..=digit_count).rev() {
+            if let Some(previous) = best[k - 1] {
+                let joltage = previous * 10 + battery;
+                best[k] = Some(best[k].map_or(joltage, |current| current.max(joltage)));
+            }
+        }
+    }
+
+    best[digit_count].unwrap()
+}
+
+pub fn solve(raw_data: &str) -> (u128, u128) {
+    let banks: Vec<Vec<u128>> = raw_data
+        .lines()
+        .map(|line| {
+            line.chars()
+                .map(|c| c.to_digit(10).unwrap() as u128)
+                .collect()
+        })
+        .collect();
+
+    (
+        banks.iter().map(|bank| max_joltage(bank, 2)).sum(),
+        banks.iter().map(|bank| max_joltage(bank, 12)).sum(),
+    )
+}
@@ -0,0 +1,20 @@
+use shared::Rng;
+
+/// Generate a `size` x `size` grid of paper rolls
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let roll_probability = rng.between(30, 80) as f64 / 100.0;
+
+    let mut output = String::new();
+    for _ in 0..size {
+        for _ in 0..size {
+            output.push(if rng.chance(roll_probability) {
+                '@'
+            } else {
+                '.'
+            });
+        }
+        output.push('\n');
+    }
+
+    output
+}
@@ -1,122 +1,87 @@
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::automaton::{self, Automaton};
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::parse::{self, ParseError, Parser};
+use shared::random;
 use shared::{Grid, Vector};
+use std::error::Error;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum Cell {
     Empty,
     Roll,
 }
 
+/// A roll with fewer than 4 rolls around it can be removed
+fn remove_rule(grid: &Grid<Cell>, pos: &Vector, cell: &Cell) -> Cell {
+    if let Cell::Roll = cell
+        && automaton::count_neighbors(grid, pos, |cell| *cell == Cell::Roll) < 4
+    {
+        return Cell::Empty;
+    }
+
+    *cell
+}
+
+type RemoveRule = fn(&Grid<Cell>, &Vector, &Cell) -> Cell;
+
 struct Solver {
-    grid: Grid<Cell>,
+    automaton: Automaton<Cell, RemoveRule>,
 }
 
 impl Solver {
     fn new(grid: Grid<Cell>) -> Self {
-        Solver { grid }
-    }
-
-    pub fn remove_rolls(&mut self) -> usize {
-        let mut to_remove = vec![];
-        for (y, row) in self.grid.rows.iter().enumerate() {
-            for (x, cell) in row.iter().enumerate() {
-                let pos = Vector {
-                    x: x as i64,
-                    y: y as i64,
-                };
-
-                if let Cell::Roll = cell {
-                    // Count rolls around this position
-                    let cells_around = self.count_rolls_around(&pos);
-                    if cells_around < 4 {
-                        to_remove.push(pos);
-                    }
-                }
-            }
+        Solver {
+            automaton: Automaton::new(grid, remove_rule),
         }
-
-        for pos in to_remove.iter() {
-            if let Some(cell) = self.grid.get_mut(pos) {
-                *cell = Cell::Empty;
-            }
-        }
-
-        to_remove.len()
     }
+
     pub fn solve_part1(&mut self) -> usize {
-        self.remove_rolls()
+        self.automaton.step().len()
     }
 
     pub fn solve_part2(&mut self) -> usize {
-        let mut count = 0;
-        loop {
-            let removed = self.remove_rolls();
-            if removed == 0 {
-                break;
-            }
-
-            count += removed;
-        }
-
-        count
+        // Rolls are only removed, so the automaton always ends up on a fixpoint
+        self.automaton.run_until_stable();
+        self.automaton.changed_counts().iter().sum()
     }
+}
 
-    fn count_rolls_around(&self, pos: &Vector) -> usize {
-        let directions = [
-            (1, 0),
-            (1, 1),
-            (0, 1),
-            (-1, 1),
-            (-1, 0),
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-        ];
-
-        let mut count = 0;
-        for (dir_x, dir_y) in directions {
-            let next_pos = Vector {
-                x: pos.x + dir_x,
-                y: pos.y + dir_y,
-            };
-
-            if next_pos.x < 0 || next_pos.y < 0 {
-                continue;
-            }
-
-            if let Some(cell) = self.grid.get(&next_pos) {
-                if let Cell::Roll = cell {
-                    count += 1;
-                }
-            }
-        }
-
-        count
-    }
+fn parse_input(raw_data: &str) -> Result<Grid<Cell>, ParseError> {
+    parse::grid(Parser::new(raw_data).lines(), |c| match c {
+        '.' => Some(Cell::Empty),
+        '@' => Some(Cell::Roll),
+        _ => None,
+    })
 }
 
-fn parse_input(raw_data: &str) -> Grid<Cell> {
-    let rows = raw_data
-        .lines()
-        .map(|line| {
-            let row: Vec<Cell> = line
-                .chars()
-                .map(|c| match c {
-                    '.' => Cell::Empty,
-                    '@' => Cell::Roll,
-                    _ => panic!("Invalid character in input"),
-                })
-                .collect();
-
-            row
-        })
-        .collect::<Vec<Vec<Cell>>>();
-
-    Grid::new(rows)
+fn fuzz_target(raw_data: &str) -> Result<(usize, usize), ParseError> {
+    let grid = parse_input(raw_data)?;
+    let part1 = Solver::new(grid.clone()).solve_part1();
+    let part2 = Solver::new(grid).solve_part2();
+    Ok((part1, part2))
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-04.txt");
-    let grid = parse_input(raw_data);
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-04.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let grid = parse_input(&raw_data)?;
 
     println!("Solver - day 04:");
 
@@ -129,11 +94,15 @@ fn main() {
     let mut solver = Solver::new(grid.clone());
     let result = solver.solve_part2();
     println!("  Part 2 - Final code: {}", result);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "..@@.@@@@.
 @@@.@.@.@@
@@ -148,7 +117,7 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let grid = parse_input(RAW_INPUT);
+        let grid = parse_input(RAW_INPUT).unwrap();
         let mut solver = Solver::new(grid);
         let result = solver.solve_part1();
         assert_eq!(result, 13);
@@ -156,10 +125,55 @@ mod tests {
 
     #[test]
     fn check_part2_result() {
-        let grid = parse_input(RAW_INPUT);
+        let grid = parse_input(RAW_INPUT).unwrap();
         let mut solver = Solver::new(grid);
         let result = solver.solve_part2();
 
         assert_eq!(result, 43);
     }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 20);
+            let grid = parse_input(&raw_data).unwrap();
+            assert_eq!(*grid.size(), Vector { x: 20, y: 20 });
+
+            // Rolls removed first are part of all the rolls removed, which can't be more than the
+            // rolls of the grid
+            let roll_count = raw_data.matches('@').count();
+            let part1 = Solver::new(grid.clone()).solve_part1();
+            let part2 = Solver::new(grid).solve_part2();
+            assert!(part1 <= part2 && part2 <= roll_count);
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=12,
+            10,
+            |input| {
+                let grid = parse_input(input).unwrap();
+                (
+                    Solver::new(grid.clone()).solve_part1(),
+                    Solver::new(grid).solve_part2(),
+                )
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 5);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, fuzz_target);
+
+        let error = fuzz_target("..@\n.@").unwrap_err();
+        assert_eq!(
+            error.message,
+            "expected 3 cells like the first row, found 2"
+        );
+    }
 }
@@ -0,0 +1,62 @@
+/// Count the rolls of a grid with less than 4 rolls around them
+fn accessible_rolls(grid: &[Vec<bool>]) -> Vec<(usize, usize)> {
+    let mut rolls = vec![];
+    for y in 0..grid.len() {
+        for x in 0..grid[y].len() {
+            if !grid[y][x] {
+                continue;
+            }
+
+            let mut neighbor_count = 0;
+            for (dx, dy) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                let neighbor = y
+                    .checked_add_signed(dy)
+                    .and_then(|ny| grid.get(ny))
+                    .and_then(|row| row.get(x.checked_add_signed(dx)?));
+                if neighbor == Some(&true) {
+                    neighbor_count += 1;
+                }
+            }
+
+            if neighbor_count < 4 {
+                rolls.push((x, y));
+            }
+        }
+    }
+
+    rolls
+}
+
+/// Brute force solver scanning the whole grid after each removal round
+pub fn solve(raw_data: &str) -> (usize, usize) {
+    let mut grid: Vec<Vec<bool>> = raw_data
+        .lines()
+        .map(|line| line.chars().map(|c| c == '@').collect())
+        .collect();
+
+    let part1 = accessible_rolls(&grid).len();
+
+    let mut part2 = 0;
+    loop {
+        let rolls = accessible_rolls(&grid);
+        if rolls.is_empty() {
+            break;
+        }
+
+        part2 += rolls.len();
+        for (x, y) in rolls {
+            grid[y][x] = false;
+        }
+    }
+
+    (part1, part2)
+}
@@ -0,0 +1,20 @@
+use shared::Rng;
+
+const MAX_ID: usize = 1_000_000_000_000_000;
+
+/// Generate `size` ranges of fresh IDs (possibly overlapping), followed by `5 * size` IDs
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+    for _ in 0..size {
+        let min = rng.between(1, MAX_ID);
+        let max = min + rng.between(0, MAX_ID / 100);
+        output.push_str(&format!("{}-{}\n", min, max));
+    }
+
+    output.push('\n');
+    for _ in 0..5 * size {
+        output.push_str(&format!("{}\n", rng.between(1, MAX_ID)));
+    }
+
+    output
+}
@@ -1,111 +1,139 @@
-use regex::Regex;
-
-#[derive(Clone)]
-struct Range {
-    min: usize,
-    max: usize,
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, LineReader, TrailingWhitespace};
+use shared::number::OverflowError;
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use shared::trace;
+use shared::{IntervalSet, Range};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+fn solve_part1(fresh_ranges: &[Range], ids: &[usize]) -> usize {
+    let fresh_ids = IntervalSet::new(fresh_ranges.iter().copied());
+    ids.iter().filter(|id| fresh_ids.contains(**id)).count()
 }
 
-impl Range {
-    fn is_inside(&self, value: usize) -> bool {
-        value >= self.min && value <= self.max
+/// Number of IDs in the set, failing if ranges cover every possible ID
+fn fresh_id_count(fresh_ids: &IntervalSet) -> Result<usize, OverflowError> {
+    for range in fresh_ids.iter() {
+        trace::event("merged_range", &[("min", &range.min), ("max", &range.max)]);
     }
 
-    fn merge(&mut self, other: &Range) -> bool {
-        if self.min <= other.max && self.max >= other.min {
-            self.min = self.min.min(other.min);
-            self.max = self.max.max(other.max);
-            true
-        } else {
-            false
-        }
-    }
+    fresh_ids.size().ok_or(OverflowError { type_name: "usize" })
+}
 
-    fn size(&self) -> usize {
-        self.max - self.min + 1
-    }
+fn solve_part2(fresh_ranges: &[Range]) -> Result<usize, OverflowError> {
+    fresh_id_count(&IntervalSet::new(fresh_ranges.iter().copied()))
 }
 
-fn solve_part1(fresh_ranges: &Vec<Range>, ids: &Vec<usize>) -> usize {
-    let mut count = 0;
-    for id in ids {
-        if fresh_ranges.iter().any(|range| range.is_inside(*id)) {
-            count += 1;
-        }
-    }
+fn parse_range(line: &mut Parser) -> Result<Range, ParseError> {
+    let range = line.range()?;
+    line.end()?;
+    Ok(range)
+}
 
-    count
+fn parse_id(line: &mut Parser) -> Result<usize, ParseError> {
+    let id = line.unsigned()?;
+    line.end()?;
+    Ok(id)
 }
 
-fn merge_ranges_rec(fresh_ranges: &Vec<Range>) -> Vec<Range> {
-    let mut merged_ranges: Vec<Range> = vec![];
-    for range in fresh_ranges {
-        let mut merged = false;
-        for merged_range in &mut merged_ranges {
-            if merged_range.merge(range) {
-                merged = true;
-                break;
-            }
-        }
+fn parse_input(raw_data: &str) -> Result<(Vec<Range>, Vec<usize>), ParseError> {
+    let parser = Parser::new(raw_data);
+    let [ranges_section, ids_section] = parser.sections()[..] else {
+        return Err(parser.error("expected a section of ranges and a section of IDs"));
+    };
+
+    let ranges = ranges_section
+        .lines()
+        .map(|mut line| parse_range(&mut line))
+        .collect::<Result<Vec<Range>, ParseError>>()?;
+
+    let ids = ids_section
+        .lines()
+        .map(|mut line| parse_id(&mut line))
+        .collect::<Result<Vec<usize>, ParseError>>()?;
+
+    Ok((ranges, ids))
+}
+
+/// Solve both parts in a single pass over the input: only the ranges are kept in memory, IDs are
+/// checked one at a time as they are read
+fn solve_stream(reader: impl BufRead) -> Result<(usize, usize), Box<dyn Error>> {
+    let mut lines = LineReader::new(reader, TrailingWhitespace::Trim);
 
-        if !merged {
-            merged_ranges.push(range.clone());
+    let mut fresh_ranges = vec![];
+    while let Some(mut line) = lines.next_line()? {
+        if line.is_empty() {
+            break;
         }
+
+        fresh_ranges.push(parse_range(&mut line)?);
     }
 
-    if merged_ranges.len() == fresh_ranges.len() {
-        return merged_ranges;
+    let fresh_ids = IntervalSet::new(fresh_ranges);
+    let mut fresh_count = 0;
+    while let Some(mut line) = lines.next_line()? {
+        if line.is_empty() {
+            continue;
+        }
+
+        if fresh_ids.contains(parse_id(&mut line)?) {
+            fresh_count += 1;
+        }
     }
 
-    // Continue until no more merges are possible
-    merge_ranges_rec(&merged_ranges)
+    Ok((fresh_count, fresh_id_count(&fresh_ids)?))
 }
 
-fn solve_part2(fresh_ranges: &Vec<Range>) -> usize {
-    let merged_ranges = merge_ranges_rec(fresh_ranges);
-    merged_ranges.iter().map(|r| r.size()).sum()
+fn solve(raw_data: &str) -> Result<(usize, usize), Box<dyn Error>> {
+    let (ranges, ids) = parse_input(raw_data)?;
+    Ok((solve_part1(&ranges, &ids), solve_part2(&ranges)?))
 }
 
-fn parse_input(raw_data: &str) -> (Vec<Range>, Vec<usize>) {
-    let re = Regex::new(r"\r\n\r\n|\n\n").unwrap();
-    let sections: Vec<&str> = re.split(raw_data).collect();
-    let ranges: Vec<Range> = sections[0]
-        .lines()
-        .map(|part| {
-            let bounds: Vec<&str> = part.split("-").collect();
-            Range {
-                min: bounds[0].parse::<usize>().unwrap(),
-                max: bounds[1].parse::<usize>().unwrap(),
-            }
-        })
-        .collect();
-
-    let ids = sections[1]
-        .lines()
-        .map(|part| part.parse::<usize>().unwrap())
-        .collect();
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    trace::enable_from_args(&args);
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
 
-    (ranges, ids)
-}
+    let raw_data = input::normalize(
+        include_str!("../input/input-05.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, solve)? {
+        return Ok(());
+    }
 
-fn main() {
-    let raw_data = include_str!("../input/input-05.txt");
-    let (ranges, ids) = parse_input(raw_data);
+    let (part1, part2) = match args.positional()[..] {
+        // Stream the input from a file given on the command line
+        [path] => solve_stream(BufReader::new(File::open(path)?))?,
+        _ => solve(&raw_data)?,
+    };
 
     println!("Solver - day 05:");
 
     // Part 1
-    let result = solve_part1(&ranges, &ids);
-    println!("  Part 1 - Final code: {}", result);
+    println!("  Part 1 - Final code: {}", part1);
 
     // Part 2
-    let result = solve_part2(&ranges);
-    println!("  Part 2 - Final code: {}", result);
+    println!("  Part 2 - Final code: {}", part2);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "3-5
 10-14
@@ -121,16 +149,79 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let (ranges, ids) = parse_input(RAW_INPUT);
+        let (ranges, ids) = parse_input(RAW_INPUT).unwrap();
         let result = solve_part1(&ranges, &ids);
         assert_eq!(result, 3);
     }
 
     #[test]
     fn check_part2_result() {
-        let (ranges, _) = parse_input(RAW_INPUT);
-        let result = solve_part2(&ranges);
+        let (ranges, _) = parse_input(RAW_INPUT).unwrap();
+        let result = solve_part2(&ranges).unwrap();
 
         assert_eq!(result, 14);
     }
+
+    #[test]
+    fn check_stream_result() {
+        let raw_data = RAW_INPUT.replace('\n', "\r\n");
+        let result = solve_stream(raw_data.as_bytes()).unwrap();
+        assert_eq!(result, (3, 14));
+    }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 20);
+            let (ranges, ids) = parse_input(&raw_data).unwrap();
+            assert_eq!((ranges.len(), ids.len()), (20, 100));
+            assert!(
+                ranges
+                    .iter()
+                    .all(|range| range.min >= 1 && range.max - range.min <= 10_000_000_000_000)
+            );
+            assert!(
+                ids.iter()
+                    .all(|id| (1..=1_000_000_000_000_000).contains(id))
+            );
+
+            let result = solve_stream(raw_data.as_bytes()).unwrap();
+            assert_eq!(
+                result,
+                (solve_part1(&ranges, &ids), solve_part2(&ranges).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=10,
+            20,
+            |input| solve(input).unwrap(),
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 5);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, solve);
+
+        let raw_data = format!("0-{}\n\n1", usize::MAX);
+        assert!(solve(&raw_data).is_err());
+        assert!(solve_stream(raw_data.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn check_trace() {
+        let (ranges, _) = parse_input(RAW_INPUT).unwrap();
+        let (_, events) = trace::capture(|| solve_part2(&ranges));
+        let events: Vec<String> = events.iter().map(|event| event.to_string()).collect();
+        assert_eq!(
+            events,
+            ["merged_range min=3 max=5", "merged_range min=10 max=20"]
+        );
+    }
 }
@@ -0,0 +1,35 @@
+/// Solver checking IDs against every range, and counting fresh IDs between consecutive range
+/// boundaries
+pub fn solve(raw_data: &str) -> (usize, usize) {
+    let (ranges, ids) = raw_data.split_once("\n\n").unwrap();
+    let ranges: Vec<(usize, usize)> = ranges
+        .lines()
+        .map(|line| {
+            let (min, max) = line.split_once('-').unwrap();
+            (min.parse().unwrap(), max.parse().unwrap())
+        })
+        .collect();
+
+    let is_fresh = |id: usize| ranges.iter().any(|(min, max)| *min <= id && id <= *max);
+
+    let part1 = ids
+        .lines()
+        .filter(|id| is_fresh(id.parse().unwrap()))
+        .count();
+
+    // All IDs between two consecutive boundaries are either fresh or not
+    let mut boundaries: Vec<usize> = ranges
+        .iter()
+        .flat_map(|(min, max)| [*min, max + 1])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let part2 = boundaries
+        .windows(2)
+        .filter(|window| is_fresh(window[0]))
+        .map(|window| window[1] - window[0])
+        .sum();
+
+    (part1, part2)
+}
@@ -0,0 +1,51 @@
+use shared::Rng;
+use std::collections::VecDeque;
+
+/// Generate a worksheet of `size` problems, each one being a column of 2 to 4 numbers (without
+/// zero digits, all aligned left or right) followed by its operation. All lines are padded with
+/// spaces to the same width.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let row_count = rng.between(2, 4);
+    let mut rows = vec![String::new(); row_count + 1];
+
+    for problem_index in 0..size.max(1) {
+        if problem_index > 0 {
+            for row in rows.iter_mut() {
+                row.push(' ');
+            }
+        }
+
+        // The widest number sets the problem width, and digit counts increase then decrease from
+        // top to bottom so that the digits of a column are never separated by spaces
+        let width = rng.between(1, 4);
+        let mut digit_counts: Vec<usize> = (0..row_count).map(|_| rng.between(1, width)).collect();
+        digit_counts[0] = width;
+        digit_counts.sort();
+        let mut ordered_counts = VecDeque::new();
+        for digit_count in digit_counts.into_iter().rev() {
+            if rng.chance(0.5) {
+                ordered_counts.push_front(digit_count);
+            } else {
+                ordered_counts.push_back(digit_count);
+            }
+        }
+
+        let left_aligned = rng.chance(0.5);
+        for (row, digit_count) in rows.iter_mut().zip(ordered_counts) {
+            let number: String = (0..digit_count)
+                .map(|_| char::from(b'0' + rng.between(1, 9) as u8))
+                .collect();
+
+            if left_aligned {
+                row.push_str(&format!("{:<width$}", number));
+            } else {
+                row.push_str(&format!("{:>width$}", number));
+            }
+        }
+
+        let operation = if rng.chance(0.5) { '+' } else { '*' };
+        rows[row_count].push_str(&format!("{:<width$}", operation));
+    }
+
+    rows.join("\n") + "\n"
+}
@@ -1,4 +1,15 @@
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::number::{self, Number, OverflowError, Precision, Solve};
+use shared::parse::{ParseError, Parser};
+use shared::random;
 use shared::{Grid, Vector};
+use std::error::Error;
 
 enum Operation {
     Add,
@@ -6,49 +17,129 @@ enum Operation {
 }
 
 impl Operation {
-    fn from_char(input: &str) -> Option<Operation> {
-        match input {
-            "+" => Some(Operation::Add),
-            "*" => Some(Operation::Mul),
-            _ => None,
+    fn apply<T: Number>(&self, value1: &T, value2: &T) -> Result<T, OverflowError> {
+        match self {
+            Operation::Add => value1.try_add(value2),
+            Operation::Mul => value1.try_mul(value2),
         }
     }
 }
 
-fn parse_input(raw_data: &str) -> (Grid<usize>, Vec<Operation>) {
-    let rows = raw_data
-        .lines()
-        .map(|line| {
-            let row: Vec<usize> = line
-                .split_whitespace()
-                .flat_map(|c| c.parse::<usize>())
-                .collect();
-
-            row
-        })
-        .filter(|row| row.len() > 0)
-        .collect::<Vec<Vec<usize>>>();
-
-    let ops: Vec<Operation> = raw_data
+/// Split the input into lines of numbers and the line of operations (the last one which is not
+/// blank)
+fn split_lines(raw_data: &str) -> Result<(Vec<Parser<'_>>, Parser<'_>), ParseError> {
+    let parser = Parser::new(raw_data);
+    let mut lines: Vec<Parser> = parser
         .lines()
-        .skip(rows.len())
-        .flat_map(|line| {
-            line.split_whitespace()
-                .filter_map(|c| Operation::from_char(c))
-                .collect::<Vec<Operation>>()
-        })
+        .filter(|line| !line.rest().trim().is_empty())
         .collect();
+    let ops_line = lines
+        .pop()
+        .ok_or_else(|| parser.error("expected a line of operations"))?;
+
+    Ok((lines, ops_line))
+}
+
+fn parse_ops(line: &mut Parser) -> Result<Vec<Operation>, ParseError> {
+    let mut ops = vec![];
+    line.skip_spaces();
+    while !line.is_empty() {
+        let op = if line.try_literal("+") {
+            Operation::Add
+        } else if line.try_literal("*") {
+            Operation::Mul
+        } else {
+            return Err(line.error("expected `+` or `*`"));
+        };
+
+        ops.push(op);
+        line.skip_spaces();
+    }
+
+    Ok(ops)
+}
 
-    (Grid::new(rows), ops)
+fn parse_input(raw_data: &str) -> Result<(Grid<usize>, Vec<Operation>), ParseError> {
+    let (number_lines, mut ops_line) = split_lines(raw_data)?;
+    let ops = parse_ops(&mut ops_line)?;
+
+    let mut rows = vec![];
+    for mut line in number_lines {
+        let mut row = vec![];
+        line.skip_spaces();
+        while !line.is_empty() {
+            row.push(line.unsigned()?);
+            line.skip_spaces();
+        }
+
+        if row.len() != ops.len() {
+            return Err(line.error(format!(
+                "expected {} numbers like operations, found {}",
+                ops.len(),
+                row.len()
+            )));
+        }
+
+        rows.push(row);
+    }
+
+    Ok((Grid::new(rows), ops))
 }
 
-fn solve_part1(grid: &Grid<usize>, ops: &Vec<Operation>) -> usize {
+/// Parse the numbers of each problem written in columns, problems being separated by blank
+/// columns
+fn parse_input_part2(raw_data: &str) -> Result<(Vec<Vec<usize>>, Vec<Operation>), ParseError> {
+    let (number_lines, mut ops_line) = split_lines(raw_data)?;
+    let ops = parse_ops(&mut ops_line)?;
+
+    let rows = number_lines
+        .iter()
+        .map(|line| line.rest().chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+
+    // Transpose rows to columns and parse values (short lines are padded with spaces, in case
+    // trailing whitespace has been stripped from the input)
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut problems: Vec<Vec<usize>> = vec![vec![]];
+    for i in 0..width {
+        let column = rows
+            .iter()
+            .map(|row| row.get(i).copied().unwrap_or(' '))
+            .collect::<String>();
+        let column = column.trim();
+
+        if column.is_empty() {
+            if problems.last().is_some_and(|problem| !problem.is_empty()) {
+                problems.push(vec![]);
+            }
+
+            continue;
+        }
+
+        let value = column.parse::<usize>().map_err(|_| {
+            ops_line.error(format!("invalid number `{}` in column {}", column, i + 1))
+        })?;
+        problems.last_mut().unwrap().push(value);
+    }
+
+    problems.retain(|problem| !problem.is_empty());
+    if problems.len() != ops.len() {
+        return Err(ops_line.error(format!(
+            "expected {} problems like operations, found {}",
+            ops.len(),
+            problems.len()
+        )));
+    }
+
+    Ok((problems, ops))
+}
+
+fn solve_part1<T: Number>(grid: &Grid<usize>, ops: &[Operation]) -> Result<T, OverflowError> {
     let grid_size = grid.size();
 
-    let mut total = 0;
-    for column_index in 0..grid_size.x as usize {
-        let mut value = 0;
-        let op = &ops[column_index];
+    let mut total = T::zero();
+    for (column_index, op) in ops.iter().enumerate().take(grid_size.x as usize) {
+        let mut value = T::zero();
         for row_index in 0..grid_size.y {
             let cell_value = grid
                 .get(&Vector {
@@ -56,108 +147,98 @@ fn solve_part1(grid: &Grid<usize>, ops: &Vec<Operation>) -> usize {
                     y: row_index,
                 })
                 .unwrap();
+            let cell_value = T::from_usize(*cell_value);
 
             if 0 == row_index {
-                value = *cell_value;
+                value = cell_value;
                 continue;
             }
 
-            match op {
-                Operation::Add => value += cell_value,
-                Operation::Mul => value *= cell_value,
-            }
+            value = op.apply(&value, &cell_value)?;
         }
 
-        total += value;
+        total = total.try_add(&value)?;
     }
 
-    total
+    Ok(total)
 }
 
-fn solve_part2(raw_data: &str) -> usize {
-    let rows = raw_data
-        .lines()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
+fn solve_part2<T: Number>(problems: &[Vec<usize>], ops: &[Operation]) -> Result<T, OverflowError> {
+    let mut total = T::zero();
+    for (problem, op) in problems.iter().zip(ops) {
+        let mut value = T::from_usize(problem[0]);
+        for cell_value in &problem[1..] {
+            value = op.apply(&value, &T::from_usize(*cell_value))?;
+        }
 
-    // Transpose rows to columns and parse values
-    // Get a sequence of: Number / Number / None / Number ... / Number / None / ...
-    let transposed_rows = (0..rows[0].len())
-        .map(|i| {
-            rows.iter()
-                .take(rows.len() - 1)
-                .map(|row| row[i])
-                .collect::<String>()
-        })
-        .map(|str| {
-            let str = str.trim();
-            if str.is_empty() {
-                return None;
-            }
+        total = total.try_add(&value)?;
+    }
 
-            Some(str.parse::<usize>().unwrap())
-        })
-        .collect::<Vec<Option<usize>>>();
+    Ok(total)
+}
 
-    let ops: Vec<Operation> = raw_data
-        .lines()
-        .skip(rows.len() - 1)
-        .flat_map(|line| {
-            line.split_whitespace()
-                .filter_map(|c| Operation::from_char(c))
-                .collect::<Vec<Operation>>()
-        })
-        .collect();
+// Adapters used to select the integer type of the results at runtime
+struct Part1<'a>(&'a Grid<usize>, &'a [Operation]);
+struct Part2<'a>(&'a [Vec<usize>], &'a [Operation]);
 
-    let mut total = 0;
-    let mut current_value = 0;
-    let mut op_index = 0;
-    let mut current_operation = &ops[op_index];
-
-    for value in transposed_rows {
-        match value {
-            Some(value) => {
-                if 0 == current_value {
-                    current_value = value;
-                    continue;
-                }
+impl Solve for Part1<'_> {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+        solve_part1(self.0, self.1)
+    }
+}
 
-                match current_operation {
-                    Operation::Add => current_value += value,
-                    Operation::Mul => current_value *= value,
-                }
-            }
-            None => {
-                total += current_value;
-                current_value = 0;
-                op_index += 1;
-                current_operation = &ops[op_index];
-            }
-        }
+impl Solve for Part2<'_> {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+        solve_part2(self.0, self.1)
     }
+}
 
-    total + current_value
+fn fuzz_target(raw_data: &str) -> Result<(), Box<dyn Error>> {
+    let (grid, ops) = parse_input(raw_data)?;
+    solve_part1::<usize>(&grid, &ops)?;
+    let (problems, ops) = parse_input_part2(raw_data)?;
+    solve_part2::<usize>(&problems, &ops)?;
+    Ok(())
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-06.txt");
-    let (grid, ops) = parse_input(raw_data);
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let precision = args.value_or("precision", Precision::Checked)?;
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-06.txt"),
+        TrailingWhitespace::Significant,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let (grid, ops) = parse_input(&raw_data)?;
 
     println!("Solver - day 06:");
 
     // Part 1
-    let result = solve_part1(&grid, &ops);
+    let result = number::solve_with(&Part1(&grid, &ops), precision)?;
     println!("  Part 1 - Final code: {}", result);
 
     // Part 2
-    let result = solve_part2(raw_data);
+    let (problems, ops) = parse_input_part2(&raw_data)?;
+    let result = number::solve_with(&Part2(&problems, &ops), precision)?;
     println!("  Part 2 - Final code: {}", result);
     // 11601712780573 - KO
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "123 328  51 64 
  45 64  387 23 
@@ -166,14 +247,105 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let (grid, ops) = parse_input(RAW_INPUT);
-        let result = solve_part1(&grid, &ops);
+        let (grid, ops) = parse_input(RAW_INPUT).unwrap();
+        let result = solve_part1::<usize>(&grid, &ops).unwrap();
         assert_eq!(result, 4277556);
     }
 
     #[test]
     fn check_part2_result() {
-        let result = solve_part2(RAW_INPUT);
+        let (problems, ops) = parse_input_part2(RAW_INPUT).unwrap();
+        let result = solve_part2::<usize>(&problems, &ops).unwrap();
         assert_eq!(result, 3263827);
     }
+
+    #[test]
+    fn check_part2_result_without_trailing_whitespace() {
+        let raw_data = RAW_INPUT.replace(" \n", "\r\n");
+        let raw_data = input::normalize(&raw_data, TrailingWhitespace::Trim);
+        let (problems, ops) = parse_input_part2(&raw_data).unwrap();
+        let result = solve_part2::<usize>(&problems, &ops).unwrap();
+        assert_eq!(result, 3263827);
+    }
+
+    #[test]
+    fn check_overflow() {
+        // 2^32 * 2^32 is one more than the largest `usize`
+        let raw_data = "4294967296\n4294967296\n*         ";
+        let (grid, ops) = parse_input(raw_data).unwrap();
+        let part1 = Part1(&grid, &ops);
+        assert!(number::solve_with(&part1, Precision::Checked).is_err());
+        for precision in [Precision::Wide, Precision::Big, Precision::Auto] {
+            assert_eq!(
+                number::solve_with(&part1, precision),
+                Ok("18446744073709551616".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn check_generated_input() {
+        // Numbers of 1 to 4 digits, without zero digits
+        let is_generated =
+            |value: &usize| (1..=9999).contains(value) && !value.to_string().contains('0');
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 20);
+            let (grid, ops) = parse_input(&raw_data).unwrap();
+            assert_eq!(ops.len(), 20);
+            let size = *grid.size();
+            assert_eq!(size.x, 20);
+            assert!((2..=4).contains(&size.y));
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    assert!(is_generated(grid.get(&Vector { x, y }).unwrap()));
+                }
+            }
+
+            // Columns of a problem are as many as the digits of its widest number
+            let (problems, ops) = parse_input_part2(&raw_data).unwrap();
+            assert_eq!((problems.len(), ops.len()), (20, 20));
+            for (index, problem) in problems.iter().enumerate() {
+                let widest = (0..size.y)
+                    .map(|y| {
+                        grid.get(&Vector { x: index as i64, y })
+                            .unwrap()
+                            .to_string()
+                            .len()
+                    })
+                    .max();
+                assert_eq!(Some(problem.len()), widest);
+                assert!(problem.iter().all(is_generated));
+            }
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=10,
+            20,
+            |input| {
+                let (grid, ops) = parse_input(input).unwrap();
+                let (problems, _) = parse_input_part2(input).unwrap();
+                (
+                    solve_part1::<u128>(&grid, &ops).unwrap(),
+                    solve_part2::<u128>(&problems, &ops).unwrap(),
+                )
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 5);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, fuzz_target);
+
+        let error = parse_input("1 2\n3\n+ *").err().unwrap();
+        assert_eq!(error.message, "expected 2 numbers like operations, found 1");
+
+        let error = parse_input_part2("1 5\n  6\n2 7\n+ *").err().unwrap();
+        assert_eq!(error.message, "invalid number `1 2` in column 1");
+    }
 }
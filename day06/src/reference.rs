@@ -0,0 +1,53 @@
+fn apply(operation: char, values: impl Iterator<Item = u128>) -> u128 {
+    match operation {
+        '+' => values.sum(),
+        _ => values.product(),
+    }
+}
+
+/// Solver reading each problem from the character grid, problems being separated by columns of
+/// spaces only
+pub fn solve(raw_data: &str) -> (u128, u128) {
+    let lines: Vec<Vec<char>> = raw_data
+        .lines()
+        .map(|line| line.chars().collect())
+        .collect();
+    let (operations, rows) = lines.split_last().unwrap();
+    let width = lines.iter().map(|line| line.len()).max().unwrap();
+    let cell = |row: &[char], x: usize| row.get(x).copied().unwrap_or(' ');
+
+    let mut problems = vec![];
+    let mut start = 0;
+    for x in 0..=width {
+        if x == width || lines.iter().all(|line| cell(line, x) == ' ') {
+            if x > start {
+                problems.push(start..x);
+            }
+            start = x + 1;
+        }
+    }
+
+    let mut part1 = 0;
+    let mut part2 = 0;
+    for columns in problems {
+        let operation = columns
+            .clone()
+            .map(|x| cell(operations, x))
+            .find(|c| *c != ' ')
+            .unwrap();
+
+        let row_values = rows.iter().map(|row| {
+            let number: String = columns.clone().map(|x| cell(row, x)).collect();
+            number.trim().parse::<u128>().unwrap()
+        });
+        part1 += apply(operation, row_values);
+
+        let column_values = columns.clone().map(|x| {
+            let number: String = rows.iter().map(|row| cell(row, x)).collect();
+            number.trim().parse::<u128>().unwrap()
+        });
+        part2 += apply(operation, column_values);
+    }
+
+    (part1, part2)
+}
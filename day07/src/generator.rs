@@ -0,0 +1,39 @@
+use shared::Rng;
+
+/// Generate a manifold of `size` rows of splitters (separated by empty lines), the beam entering
+/// at the middle of the first line. Splitters are only placed where a beam can reach them.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let width = 2 * size + 1;
+    let middle = size;
+    let splitter_probability = rng.between(30, 90) as f64 / 100.0;
+
+    let mut output = String::new();
+    for row_index in 0..size {
+        let row: String = (0..width)
+            .map(|x| {
+                if row_index == 0 {
+                    return if x == middle { 'S' } else { '.' };
+                }
+
+                // Like in puzzle inputs, splitters are on every other column (alternating between
+                // rows), within the range the beams can reach
+                let distance = x.abs_diff(middle);
+                if distance < row_index
+                    && (distance + row_index) % 2 == 1
+                    && rng.chance(splitter_probability)
+                {
+                    '^'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+
+        output.push_str(&row);
+        output.push('\n');
+        output.push_str(&".".repeat(width));
+        output.push('\n');
+    }
+
+    output
+}
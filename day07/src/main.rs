@@ -1,5 +1,16 @@
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::number::{self, Number, OverflowError, Precision, Solve};
+use shared::parse::{self, ParseError, Parser};
+use shared::random;
 use shared::{Grid, Vector};
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
 
 #[derive(Clone, PartialEq)]
 enum Cell {
@@ -29,7 +40,7 @@ impl Solver {
         beams.insert(start_pos);
 
         let down = Vector { x: 0, y: 1 };
-        for _step in 0..grid_size.y - 1{
+        for _step in 0..grid_size.y - 1 {
             let mut next_beams: HashSet<Vector> = HashSet::new();
             for beam_pos in beams.iter() {
                 let next_pos = *beam_pos + down;
@@ -67,21 +78,21 @@ impl Solver {
         collision_count
     }
 
-    pub fn solve_part2(&self) -> usize {
+    pub fn solve_part2<T: Number>(&self) -> Result<T, OverflowError> {
         let grid_size = self.grid.size();
 
         let start_pos = Vector {
             x: grid_size.x / 2,
             y: 0,
         };
-        
+
         // Hashmap of beam positions -> number of timelines for that position
-        let mut beams: HashMap<Vector, usize> = HashMap::new();
-        beams.insert(start_pos, 1);
+        let mut beams: HashMap<Vector, T> = HashMap::new();
+        beams.insert(start_pos, T::from_usize(1));
 
         let down = Vector { x: 0, y: 1 };
-        for _step in 0..grid_size.y - 1{
-            let mut next_beams: HashMap<Vector, usize> = HashMap::with_capacity(beams.len());
+        for _step in 0..grid_size.y - 1 {
+            let mut next_beams: HashMap<Vector, T> = HashMap::with_capacity(beams.len());
             for (beam_pos, timeline_count) in beams.iter() {
                 let next_pos = *beam_pos + down;
 
@@ -93,8 +104,7 @@ impl Solver {
                     };
 
                     if self.grid.is_inside(&left_pos) {
-                        let entry = next_beams.entry(left_pos).or_insert(0);
-                        *entry += timeline_count;
+                        add_timelines(&mut next_beams, left_pos, timeline_count)?;
                     }
 
                     let right_pos = Vector {
@@ -103,47 +113,75 @@ impl Solver {
                     };
 
                     if self.grid.is_inside(&right_pos) {
-                        let entry = next_beams.entry(right_pos).or_insert(0);
-                        *entry += timeline_count;
+                        add_timelines(&mut next_beams, right_pos, timeline_count)?;
                     }
                 } else {
-                    let entry = next_beams.entry(next_pos).or_insert(0);
-                    *entry += timeline_count;
+                    add_timelines(&mut next_beams, next_pos, timeline_count)?;
                 }
             }
 
             beams = next_beams;
         }
 
-        beams.iter().map(|(_pos, count)| count).sum()
+        beams
+            .values()
+            .try_fold(T::zero(), |total, count| total.try_add(count))
     }
 }
 
-fn parse_input(raw_data: &str) -> Grid<Cell> {
-    let rows = raw_data
-        .lines()
-        .enumerate()
-        .filter(|(index, _)| index % 2 == 0) // Remove odd lines (always empty)
-        .map(|(_, line)| {
-            let row: Vec<Cell> = line
-                .chars()
-                .map(|c| match c {
-                    '.' | 'S' => Cell::Empty,
-                    '^' => Cell::Splitter,
-                    _ => panic!("Invalid character in input"),
-                })
-                .collect();
+fn add_timelines<T: Number>(
+    beams: &mut HashMap<Vector, T>,
+    pos: Vector,
+    timeline_count: &T,
+) -> Result<(), OverflowError> {
+    let entry = beams.entry(pos).or_insert_with(T::zero);
+    *entry = entry.try_add(timeline_count)?;
+    Ok(())
+}
 
-            row
-        })
-        .collect::<Vec<Vec<Cell>>>();
+// Adapter used to select the integer type of the result at runtime
+struct Timelines<'a>(&'a Solver);
+
+impl Solve for Timelines<'_> {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+        self.0.solve_part2()
+    }
+}
+
+fn parse_input(raw_data: &str) -> Result<Grid<Cell>, ParseError> {
+    // Remove odd lines (always empty)
+    let lines = Parser::new(raw_data).lines().step_by(2);
+    parse::grid(lines, |c| match c {
+        '.' | 'S' => Some(Cell::Empty),
+        '^' => Some(Cell::Splitter),
+        _ => None,
+    })
+}
 
-    Grid::new(rows)
+fn fuzz_target(raw_data: &str) -> Result<(), Box<dyn Error>> {
+    let solver = Solver::new(parse_input(raw_data)?);
+    solver.solve_part1();
+    solver.solve_part2::<usize>()?;
+    Ok(())
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-07.txt");
-    let grid = parse_input(raw_data);
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let precision = args.value_or("precision", Precision::Checked)?;
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-07.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let grid = parse_input(&raw_data)?;
     let solver = Solver::new(grid);
 
     println!("Solver - day 07:");
@@ -153,13 +191,17 @@ fn main() {
     println!("  Part 1 - Final code: {}", result);
 
     // Part 2
-    let result = solver.solve_part2();
+    let result = number::solve_with(&Timelines(&solver), precision)?;
     println!("  Part 2 - Final code: {}", result);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = ".......S.......
 ...............
@@ -180,7 +222,7 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let grid = parse_input(RAW_INPUT);
+        let grid = parse_input(RAW_INPUT).unwrap();
         let solver = Solver::new(grid);
         let result = solver.solve_part1();
         assert_eq!(result, 21);
@@ -188,10 +230,95 @@ mod tests {
 
     #[test]
     fn check_part2_result() {
-        let grid = parse_input(RAW_INPUT);
+        let grid = parse_input(RAW_INPUT).unwrap();
         let solver = Solver::new(grid);
-        let result = solver.solve_part2();
+        let result = solver.solve_part2::<usize>().unwrap();
 
         assert_eq!(result, 40);
     }
+
+    #[test]
+    fn check_overflow() {
+        // A full triangle of 70 rows of splitters doubles the timelines on each row
+        let row_count = 70;
+        let width = 2 * row_count + 3;
+        let middle = row_count + 1;
+        let mut raw_data: String = (0..width)
+            .map(|x| if x == middle { 'S' } else { '.' })
+            .collect();
+        raw_data.push('\n');
+        for row in 1..=row_count {
+            raw_data.push_str(&".".repeat(width));
+            raw_data.push('\n');
+            let line: String = (0..width)
+                .map(|x| {
+                    let distance = x.abs_diff(middle);
+                    if distance < row && (row - 1 - distance) % 2 == 0 {
+                        '^'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            raw_data.push_str(&line);
+            raw_data.push('\n');
+        }
+
+        let solver = Solver::new(parse_input(&raw_data).unwrap());
+        let timelines = Timelines(&solver);
+        assert!(number::solve_with(&timelines, Precision::Checked).is_err());
+        for precision in [Precision::Wide, Precision::Big, Precision::Auto] {
+            assert_eq!(
+                number::solve_with(&timelines, precision),
+                Ok((1_u128 << 70).to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 20);
+            let grid = parse_input(&raw_data).unwrap();
+            assert_eq!(*grid.size(), Vector { x: 41, y: 20 });
+
+            // Splitters are within the reach of the beam, entering at the middle of the first row
+            for y in 0..20 {
+                for x in 0..41_i64 {
+                    if grid.get(&Vector { x, y }) == Some(&Cell::Splitter) {
+                        assert!(x.abs_diff(20) < y as u64);
+                    }
+                }
+            }
+
+            // Each splitter reached creates at least one new timeline
+            let solver = Solver::new(grid);
+            let part1 = solver.solve_part1();
+            let part2 = solver.solve_part2::<usize>().unwrap();
+            assert!(part1 < part2);
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=12,
+            20,
+            |input| {
+                let solver = Solver::new(parse_input(input).unwrap());
+                (solver.solve_part1(), solver.solve_part2::<usize>().unwrap())
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 5);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, fuzz_target);
+
+        let error = parse_input("..S..\n.....\n..x..").err().unwrap();
+        assert_eq!((error.line, error.column), (3, 3));
+    }
 }
@@ -0,0 +1,40 @@
+use std::collections::HashSet;
+
+/// Follow a single timeline from a position, splitting it recursively (without any caching), and
+/// record the splitters it hits
+fn follow(grid: &[Vec<char>], x: usize, y: usize, hit: &mut HashSet<(usize, usize)>) -> usize {
+    if y + 1 >= grid.len() {
+        return 1;
+    }
+
+    if grid[y + 1][x] != '^' {
+        return follow(grid, x, y + 1, hit);
+    }
+
+    hit.insert((x, y + 1));
+    let mut timeline_count = 0;
+    if x > 0 {
+        timeline_count += follow(grid, x - 1, y + 1, hit);
+    }
+    if x + 1 < grid[y].len() {
+        timeline_count += follow(grid, x + 1, y + 1, hit);
+    }
+
+    timeline_count
+}
+
+/// Brute force solver enumerating all timelines one by one
+pub fn solve(raw_data: &str) -> (usize, usize) {
+    // Odd lines are always empty
+    let grid: Vec<Vec<char>> = raw_data
+        .lines()
+        .step_by(2)
+        .map(|line| line.chars().collect())
+        .collect();
+
+    let start = grid[0].iter().position(|c| *c == 'S').unwrap();
+    let mut hit = HashSet::new();
+    let timeline_count = follow(&grid, start, 0, &mut hit);
+
+    (hit.len(), timeline_count)
+}
@@ -0,0 +1,25 @@
+use shared::Rng;
+use std::collections::HashSet;
+
+// Part 1 makes 1000 connections, which needs at least 46 junction boxes
+const MIN_BOX_COUNT: usize = 46;
+
+/// Generate `size` junction boxes (at least 46) at distinct positions
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut positions = HashSet::new();
+
+    let mut output = String::new();
+    while positions.len() < size.max(MIN_BOX_COUNT) {
+        let pos = (
+            rng.between(0, 99_999),
+            rng.between(0, 99_999),
+            rng.between(0, 99_999),
+        );
+
+        if positions.insert(pos) {
+            output.push_str(&format!("{},{},{}\n", pos.0, pos.1, pos.2));
+        }
+    }
+
+    output
+}
@@ -1,4 +1,18 @@
-use std::collections::HashSet;
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::UnionFind;
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use shared::trace;
+use std::error::Error;
+
+// Squared distances between boxes must fit in an i64
+const MAX_COORDINATE: i64 = 1_000_000_000;
 
 #[derive(Clone, Copy, Hash, Eq, PartialEq, Debug)]
 struct Vector3 {
@@ -57,66 +71,49 @@ impl Solver {
         }
 
         // Sort by distances
-        connections.sort_by(|a, b| a.squared_distance.cmp(&b.squared_distance));
+        connections.sort_by_key(|connection| connection.squared_distance);
 
         let connection_count = match part {
             Part::Part1(count) => count,
             Part::Part2 => connections.len(),
         };
 
-        let mut circuits: Vec<HashSet<usize>> = vec![];
-        let mut connected_circuits: Vec<usize> = vec![];
-        for connection in &connections[0..connection_count] {
-            // Check if there is already some circuits containing either of the boxes
-            connected_circuits.clear();
-            for (index, circuit) in circuits.iter().enumerate() {
-                if circuit.contains(&connection.box_id1) || circuit.contains(&connection.box_id2) {
-                    connected_circuits.push(index);
-                }
-            }
-
-            if connected_circuits.len() > 1 {
-                // Connection created between multiple circuits -> merge them
-                let mut merged_circuit = HashSet::from([connection.box_id1, connection.box_id2]);
-                connected_circuits.sort();
-                connected_circuits.reverse();
-                for group_index in &connected_circuits {
-                    let circuit = &circuits[*group_index];
-                    merged_circuit.extend(circuit);
-                    circuits.remove(*group_index);
-                }
-
-                circuits.push(merged_circuit);
-            } else if connected_circuits.len() == 1 {
-                // Add connection to the existing circuit
-                let group_index = connected_circuits[0];
-                let circuit = &mut circuits[group_index];
-                circuit.insert(connection.box_id1);
-                circuit.insert(connection.box_id2);
-            } else {
-                // Create a new circuit
-                let new_group = HashSet::from([connection.box_id1, connection.box_id2]);
-                circuits.push(new_group);
+        let mut circuits = UnionFind::new(self.boxes.len());
+        for connection in connections.iter().take(connection_count) {
+            if circuits.union(connection.box_id1, connection.box_id2) {
+                trace::event(
+                    "merge",
+                    &[
+                        ("box1", &connection.box_id1),
+                        ("box2", &connection.box_id2),
+                        ("squared_distance", &connection.squared_distance),
+                        ("circuit_count", &circuits.component_count()),
+                    ],
+                );
             }
 
-            if let Part::Part2 = part {
-                // Check if we have a single circuit containing all boxes
-                if let [circuit] = &circuits[..]
-                    && circuit.len() == self.boxes.len()
-                {
-                    let pos1 = &self.boxes[connection.box_id1];
-                    let pos2 = &self.boxes[connection.box_id2];
-                    return (pos1.x * pos2.x) as usize;
-                }
+            // Check if we have a single circuit containing all boxes
+            if let Part::Part2 = part
+                && circuits.component_count() == 1
+            {
+                let pos1 = &self.boxes[connection.box_id1];
+                let pos2 = &self.boxes[connection.box_id2];
+                return (pos1.x * pos2.x) as usize;
             }
         }
 
         if let Part::Part1(_) = part {
-            circuits.sort_by(|h1, h2| h1.len().cmp(&h2.len()));
-            return circuits.iter().rev().take(3).map(|g| g.len()).product();
+            let mut circuit_sizes: Vec<usize> = circuits
+                .components()
+                .iter()
+                .map(|circuit| circuit.len())
+                .collect();
+
+            circuit_sizes.sort();
+            return circuit_sizes.iter().rev().take(3).product();
         }
 
-        panic!("No solution found");
+        unreachable!("Boxes are all connected once every connection is made");
     }
 
     pub fn solve_part1(&mut self, connection_count: usize) -> usize {
@@ -128,27 +125,51 @@ impl Solver {
     }
 }
 
-fn parse_input(raw_data: &str) -> Vec<Vector3> {
-    raw_data
+fn parse_input(raw_data: &str) -> Result<Vec<Vector3>, ParseError> {
+    let parser = Parser::new(raw_data);
+    let boxes = parser
         .lines()
-        .map(|line| {
-            let coords: Vec<i64> = line
-                .split(',')
-                .map(|num| num.trim().parse().unwrap())
-                .collect();
+        .map(|mut line| {
+            let x = line.bounded(0..=MAX_COORDINATE)?;
+            line.literal(",")?;
+            let y = line.bounded(0..=MAX_COORDINATE)?;
+            line.literal(",")?;
+            let z = line.bounded(0..=MAX_COORDINATE)?;
+            line.end()?;
 
-            Vector3 {
-                x: coords[0],
-                y: coords[1],
-                z: coords[2],
-            }
+            Ok(Vector3 { x, y, z })
         })
-        .collect()
+        .collect::<Result<Vec<Vector3>, ParseError>>()?;
+
+    // A single box can never be connected
+    if boxes.len() < 2 {
+        return Err(parser.error("expected at least 2 junction boxes"));
+    }
+
+    Ok(boxes)
+}
+
+fn fuzz_target(raw_data: &str) -> Result<(usize, usize), ParseError> {
+    let mut solver = Solver::new(parse_input(raw_data)?);
+    Ok((solver.solve_part1(1000), solver.solve_part2()))
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-08.txt");
-    let junctions = parse_input(raw_data);
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    trace::enable_from_args(&args);
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-08.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let junctions = parse_input(&raw_data)?;
     let mut solver = Solver::new(junctions);
 
     println!("Solver - day 08:");
@@ -160,11 +181,16 @@ fn main() {
     // Part 2
     let result = solver.solve_part2();
     println!("  Part 2 - Final code: {}", result);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
+    use std::collections::HashSet;
 
     const RAW_INPUT: &str = "162,817,812
 57,618,57
@@ -189,7 +215,7 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let junctions = parse_input(RAW_INPUT);
+        let junctions = parse_input(RAW_INPUT).unwrap();
         let mut solver = Solver::new(junctions);
         let result = solver.solve_part1(10);
         assert_eq!(result, 40);
@@ -197,10 +223,64 @@ mod tests {
 
     #[test]
     fn check_part2_result() {
-        let junctions = parse_input(RAW_INPUT);
+        let junctions = parse_input(RAW_INPUT).unwrap();
         let mut solver = Solver::new(junctions);
         let result = solver.solve_part2();
 
         assert_eq!(result, 25272);
     }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..5 {
+            // At least 46 boxes, enough for the 1000 connections of part 1
+            let raw_data = generator::generate(&mut Rng::new(seed), 10);
+            let boxes = parse_input(&raw_data).unwrap();
+            assert_eq!(boxes.len(), 46);
+            assert_eq!(boxes.iter().collect::<HashSet<_>>().len(), 46);
+            assert!(boxes.iter().all(|pos| {
+                [pos.x, pos.y, pos.z]
+                    .iter()
+                    .all(|coordinate| (0..100_000).contains(coordinate))
+            }));
+
+            let raw_data = generator::generate(&mut Rng::new(seed), 100);
+            assert_eq!(parse_input(&raw_data).unwrap().len(), 100);
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            46..=50,
+            5,
+            |input| {
+                let mut solver = Solver::new(parse_input(input).unwrap());
+                (solver.solve_part1(1000), solver.solve_part2())
+            },
+            |input| reference::solve(input, 1000),
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        fuzz::assert_no_crash(&[RAW_INPUT], 1000, fuzz_target);
+
+        let error = parse_input("1,2,3\n").unwrap_err();
+        assert_eq!(error.message, "expected at least 2 junction boxes");
+        assert!(parse_input("1,2,3\n1,2,-3").is_err());
+    }
+
+    #[test]
+    fn check_trace() {
+        let junctions = parse_input(RAW_INPUT).unwrap();
+        let mut solver = Solver::new(junctions);
+        let (_, events) = trace::capture(|| solver.solve_part2());
+
+        // Each merge joins two circuits, until a single one is left
+        assert_eq!(events.len(), 19);
+        assert_eq!(events[0].field("squared_distance"), Some("100427"));
+        assert_eq!(events[18].field("circuit_count"), Some("1"));
+    }
 }
@@ -0,0 +1,50 @@
+/// Brute force solver tracking the circuit of each junction box with a label, relabelling a whole
+/// circuit at each connection
+pub fn solve(raw_data: &str, connection_count: usize) -> (usize, usize) {
+    let boxes: Vec<Vec<i64>> = raw_data
+        .lines()
+        .map(|line| {
+            line.split(',')
+                .map(|value| value.parse().unwrap())
+                .collect()
+        })
+        .collect();
+
+    let mut pairs = vec![];
+    for i in 0..boxes.len() {
+        for j in (i + 1)..boxes.len() {
+            let distance: i64 = (0..3)
+                .map(|axis| (boxes[i][axis] - boxes[j][axis]).pow(2))
+                .sum();
+            pairs.push((distance, i, j));
+        }
+    }
+    pairs.sort();
+
+    let mut labels: Vec<usize> = (0..boxes.len()).collect();
+    let mut part1 = 0;
+    let mut part2 = None;
+    for (index, (_, i, j)) in pairs.iter().enumerate() {
+        let (old_label, new_label) = (labels[*j], labels[*i]);
+        for label in labels.iter_mut() {
+            if *label == old_label {
+                *label = new_label;
+            }
+        }
+
+        if index + 1 == connection_count {
+            let mut sizes: Vec<usize> = (0..boxes.len())
+                .map(|label| labels.iter().filter(|l| **l == label).count())
+                .filter(|size| *size > 0)
+                .collect();
+            sizes.sort();
+            part1 = sizes.iter().rev().take(3).product();
+        }
+
+        if part2.is_none() && labels.iter().all(|label| *label == labels[0]) {
+            part2 = Some((boxes[*i][0] * boxes[*j][0]) as usize);
+        }
+    }
+
+    (part1, part2.unwrap())
+}
@@ -0,0 +1,30 @@
+use shared::Rng;
+
+const BOTTOM: usize = 100_000;
+
+/// Generate the red tiles of a polygon made of `size` vertical strips of random widths and
+/// heights, standing on a common horizontal edge. Tiles are listed in order along the edges.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut x = rng.between(0, 1_000);
+    let mut tiles = vec![(x, BOTTOM)];
+
+    let mut last_top = BOTTOM;
+    for _ in 0..size.max(1) {
+        // Two consecutive strips can't have the same top, the tiles would not be corners
+        let mut top = rng.between(0, BOTTOM - 1);
+        while top == last_top {
+            top = rng.between(0, BOTTOM - 1);
+        }
+
+        tiles.push((x, top));
+        x += rng.between(1, 2_000);
+        tiles.push((x, top));
+        last_top = top;
+    }
+    tiles.push((x, BOTTOM));
+
+    tiles
+        .iter()
+        .map(|(x, y)| format!("{},{}\n", x, y))
+        .collect()
+}
@@ -1,5 +1,18 @@
-use shared::{Grid, Vector};
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use shared::{Grid, SummedAreaTable, Vector};
 use std::collections::HashSet;
+use std::error::Error;
+
+// Areas between tiles must fit in an i64
+const MAX_COORDINATE: i64 = 1_000_000_000;
 
 #[derive(Clone, PartialEq)]
 enum Cell {
@@ -28,19 +41,6 @@ impl Solver {
         ((pos1.x - pos2.x).abs() + 1) * ((pos1.y - pos2.y).abs() + 1)
     }
 
-    fn boundaries(pos1: &Vector, pos2: &Vector) -> [Vector; 2] {
-        [
-            Vector {
-                x: pos1.x.min(pos2.x),
-                y: pos1.y.min(pos2.y),
-            },
-            Vector {
-                x: pos1.x.max(pos2.x),
-                y: pos1.y.max(pos2.y),
-            },
-        ]
-    }
-
     pub fn solve_part1(&self) -> usize {
         let mut max_area: i64 = 0;
         for i in 0..self.red_tiles.len() - 1 {
@@ -100,7 +100,7 @@ impl Solver {
         }
     }
 
-    fn compress(pos: &Vector, x_values: &Vec<i64>, y_values: &Vec<i64>) -> Vector {
+    fn compress(pos: &Vector, x_values: &[i64], y_values: &[i64]) -> Vector {
         Vector {
             x: x_values.iter().position(|&x| x == pos.x).unwrap() as i64,
             y: y_values.iter().position(|&y| y == pos.y).unwrap() as i64,
@@ -108,12 +108,16 @@ impl Solver {
     }
 
     pub fn solve_part2(&self) -> usize {
-        // Compress positions to reduce complexity
-        let x_values: HashSet<i64> = HashSet::from_iter(self.red_tiles.iter().map(|pos| pos.x));
+        // Compress positions to reduce complexity: each compressed cell is a block of tiles,
+        // starting at a tile coordinate and ending before the next one. The tile after each red
+        // tile also starts a block, so that gaps between two lines are never lost.
+        let x_values: HashSet<i64> =
+            HashSet::from_iter(self.red_tiles.iter().flat_map(|pos| [pos.x, pos.x + 1]));
         let mut x_values: Vec<i64> = x_values.into_iter().collect();
         x_values.sort();
 
-        let y_values: HashSet<i64> = HashSet::from_iter(self.red_tiles.iter().map(|pos| pos.y));
+        let y_values: HashSet<i64> =
+            HashSet::from_iter(self.red_tiles.iter().flat_map(|pos| [pos.y, pos.y + 1]));
         let mut y_values: Vec<i64> = y_values.into_iter().collect();
         y_values.sort();
 
@@ -124,26 +128,26 @@ impl Solver {
 
         let mut compressed_grid = Grid::with_capacity(compressed_grid_size, Cell::Unknown);
 
-        // Add lines to the grid
-        for i in 0..self.red_tiles.len() - 1 {
-            for j in (i + 1)..self.red_tiles.len() {
-                let pos1 = Self::compress(&self.red_tiles[i], &x_values, &y_values);
-                let pos2 = Self::compress(&self.red_tiles[j], &x_values, &y_values);
-                compressed_grid[&pos1] = Cell::Occupied;
-                compressed_grid[&pos2] = Cell::Occupied;
-
-                if pos1.x == pos2.x {
-                    // Vertical line
-                    let x = pos1.x;
-                    for y in pos1.y.min(pos2.y)..=pos1.y.max(pos2.y) {
-                        compressed_grid[&Vector { x, y }] = Cell::Occupied;
-                    }
-                } else if pos1.y == pos2.y {
-                    // Horizontal line
-                    let y = pos1.y;
-                    for x in pos1.x.min(pos2.x)..=pos1.x.max(pos2.x) {
-                        compressed_grid[&Vector { x, y }] = Cell::Occupied;
-                    }
+        // Add lines to the grid, each red tile being connected to the next one (the last one
+        // being connected to the first one)
+        for i in 0..self.red_tiles.len() {
+            let j = (i + 1) % self.red_tiles.len();
+            let pos1 = Self::compress(&self.red_tiles[i], &x_values, &y_values);
+            let pos2 = Self::compress(&self.red_tiles[j], &x_values, &y_values);
+            compressed_grid[&pos1] = Cell::Occupied;
+            compressed_grid[&pos2] = Cell::Occupied;
+
+            if pos1.x == pos2.x {
+                // Vertical line
+                let x = pos1.x;
+                for y in pos1.y.min(pos2.y)..=pos1.y.max(pos2.y) {
+                    compressed_grid[&Vector { x, y }] = Cell::Occupied;
+                }
+            } else if pos1.y == pos2.y {
+                // Horizontal line
+                let y = pos1.y;
+                for x in pos1.x.min(pos2.x)..=pos1.x.max(pos2.x) {
+                    compressed_grid[&Vector { x, y }] = Cell::Occupied;
                 }
             }
         }
@@ -158,33 +162,22 @@ impl Solver {
         }
 
         // Find largest area with red corners and only "occupied" tiles
+        let occupied_table =
+            SummedAreaTable::count(&compressed_grid, |cell| *cell == Cell::Occupied);
+
         let mut max_area = 0;
         for i in 0..self.red_tiles.len() - 1 {
             for j in (i + 1)..self.red_tiles.len() {
                 let pos1 = &self.red_tiles[i];
                 let pos2 = &self.red_tiles[j];
-                let area = Self::area(&pos1, &pos2);
+                let area = Self::area(pos1, pos2);
                 if area < max_area {
                     continue;
                 }
 
                 let pos1 = Self::compress(pos1, &x_values, &y_values);
                 let pos2 = Self::compress(pos2, &x_values, &y_values);
-                let boundaries = Self::boundaries(&pos1, &pos2);
-                let mut all_occupied = true;
-                for x in boundaries[0].x..=boundaries[1].x {
-                    for y in boundaries[0].y..=boundaries[1].y {
-                        let compressed_pos = Vector { x, y };
-                        if let Some(Cell::Empty) | Some(Cell::Unknown) =
-                            compressed_grid.get(&compressed_pos)
-                        {
-                            all_occupied = false;
-                            break;
-                        }
-                    }
-                }
-
-                if all_occupied {
+                if occupied_table.is_full(&pos1, &pos2) {
                     max_area = area;
                 }
             }
@@ -194,24 +187,61 @@ impl Solver {
     }
 }
 
-fn parse_input(raw_data: &str) -> Vec<Vector> {
-    let rows = raw_data
-        .lines()
-        .map(|line| {
-            let parts = line.split(',').collect::<Vec<&str>>();
-            Vector {
-                x: parts[0].parse::<i64>().unwrap(),
-                y: parts[1].parse::<i64>().unwrap(),
-            }
-        })
-        .collect::<Vec<Vector>>();
+/// Consecutive red tiles are on the same row or column
+fn is_aligned(tile1: &Vector, tile2: &Vector) -> bool {
+    tile1.x == tile2.x || tile1.y == tile2.y
+}
 
-    rows
+fn parse_input(raw_data: &str) -> Result<Vec<Vector>, ParseError> {
+    let parser = Parser::new(raw_data);
+    let mut red_tiles: Vec<Vector> = vec![];
+    let mut last_line = parser;
+    for mut line in parser.lines() {
+        let x = line.bounded(0..=MAX_COORDINATE)?;
+        line.literal(",")?;
+        let y = line.bounded(0..=MAX_COORDINATE)?;
+        line.end()?;
+
+        let tile = Vector { x, y };
+        if let Some(previous) = red_tiles.last()
+            && !is_aligned(previous, &tile)
+        {
+            return Err(line.error("red tile not on the same row or column as the previous one"));
+        }
+
+        red_tiles.push(tile);
+        last_line = line;
+    }
+
+    match (red_tiles.first(), red_tiles.last()) {
+        (Some(first), Some(last)) if !is_aligned(first, last) => {
+            Err(last_line.error("last red tile not on the same row or column as the first one"))
+        }
+        (None, _) => Err(parser.error("expected at least one red tile")),
+        _ => Ok(red_tiles),
+    }
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-09.txt");
-    let grid = parse_input(raw_data);
+fn fuzz_target(raw_data: &str) -> Result<(usize, usize), ParseError> {
+    let solver = Solver::new(parse_input(raw_data)?);
+    Ok((solver.solve_part1(), solver.solve_part2()))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-09.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let grid = parse_input(&raw_data)?;
     let solver = Solver::new(grid);
 
     println!("Solver - day 09:");
@@ -223,12 +253,16 @@ fn main() {
     // Part 2
     let result = solver.solve_part2();
     println!("  Part 2 - Final code: {}", result);
-    // 4582310446
+    // 4582310446 - KO
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "7,1
 11,1
@@ -241,18 +275,70 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let grid = parse_input(RAW_INPUT);
+        let grid = parse_input(RAW_INPUT).unwrap();
         let solver = Solver::new(grid);
         let result = solver.solve_part1();
         assert_eq!(result, 50);
     }
 
+    #[test]
+    fn check_part2_notch() {
+        // A U shape: tiles on the same row are not connected across the notch, which is a single
+        // column wide
+        let raw_data = "1,1\n3,1\n3,5\n5,5\n5,1\n7,1\n7,7\n1,7";
+        let solver = Solver::new(parse_input(raw_data).unwrap());
+        assert_eq!(solver.solve_part2(), 21);
+        assert_eq!(reference::solve(raw_data), (49, 21));
+    }
+
     #[test]
     fn check_part2_result() {
-        let grid = parse_input(RAW_INPUT);
+        let grid = parse_input(RAW_INPUT).unwrap();
         let solver = Solver::new(grid);
         let result = solver.solve_part2();
 
         assert_eq!(result, 24);
     }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 20);
+            let red_tiles = parse_input(&raw_data).unwrap();
+
+            // Two corners per strip, and two on the bottom edge
+            assert_eq!(red_tiles.len(), 42);
+            assert_eq!(red_tiles[0].y, 100_000);
+            assert_eq!(red_tiles[41].y, 100_000);
+            assert!(red_tiles.windows(2).all(|tiles| tiles[0].x <= tiles[1].x));
+
+            // The largest rectangle inside the polygon is one of all the rectangles
+            let solver = Solver::new(red_tiles);
+            assert!(solver.solve_part2() <= solver.solve_part1());
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=8,
+            20,
+            |input| {
+                let solver = Solver::new(parse_input(input).unwrap());
+                (solver.solve_part1(), solver.solve_part2())
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 5);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, fuzz_target);
+
+        let error = parse_input("1,1\n1,5\n4,5\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 4));
+        assert!(parse_input("").is_err());
+    }
 }
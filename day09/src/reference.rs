@@ -0,0 +1,75 @@
+type Tile = (i64, i64);
+
+/// Check if a tile is on the loop of red and green tiles, or inside it
+fn is_inside(tile: Tile, polygon: &[Tile]) -> bool {
+    let (x, y) = tile;
+    let mut crossing_count = 0;
+    for (index, (x1, y1)) in polygon.iter().enumerate() {
+        let (x2, y2) = polygon[(index + 1) % polygon.len()];
+        let on_edge =
+            x1.min(&x2) <= &x && x <= *x1.max(&x2) && y1.min(&y2) <= &y && y <= *y1.max(&y2);
+        if on_edge {
+            return true;
+        }
+
+        // Cast a ray to the right, counting the vertical edges it crosses
+        if *x1 == x2 && *x1 > x && *y1.min(&y2) <= y && y < *y1.max(&y2) {
+            crossing_count += 1;
+        }
+    }
+
+    crossing_count % 2 == 1
+}
+
+/// Solver checking every tile of each rectangle. To stay fast, tiles are grouped into blocks of
+/// consecutive tiles between red tile coordinates, which are either all inside the loop or not.
+pub fn solve(raw_data: &str) -> (usize, usize) {
+    let red_tiles: Vec<Tile> = raw_data
+        .lines()
+        .map(|line| {
+            let (x, y) = line.split_once(',').unwrap();
+            (x.parse().unwrap(), y.parse().unwrap())
+        })
+        .collect();
+
+    let boundaries = |coordinate: fn(&Tile) -> i64| {
+        let mut values: Vec<i64> = red_tiles
+            .iter()
+            .flat_map(|tile| [coordinate(tile), coordinate(tile) + 1])
+            .collect();
+        values.sort();
+        values.dedup();
+        values
+    };
+    let xs = boundaries(|tile| tile.0);
+    let ys = boundaries(|tile| tile.1);
+
+    // Blocks are identified by their first tile
+    let inside: Vec<Vec<bool>> = ys
+        .iter()
+        .map(|y| xs.iter().map(|x| is_inside((*x, *y), &red_tiles)).collect())
+        .collect();
+
+    let mut part1 = 0;
+    let mut part2 = 0;
+    for (i, tile1) in red_tiles.iter().enumerate() {
+        for tile2 in &red_tiles[i + 1..] {
+            let area = ((tile1.0 - tile2.0).abs() + 1) * ((tile1.1 - tile2.1).abs() + 1);
+            part1 = part1.max(area);
+
+            let block_index = |values: &[i64], value: i64| values.binary_search(&value).unwrap();
+            let x_blocks =
+                block_index(&xs, tile1.0.min(tile2.0))..block_index(&xs, tile1.0.max(tile2.0) + 1);
+            let y_blocks =
+                block_index(&ys, tile1.1.min(tile2.1))..block_index(&ys, tile1.1.max(tile2.1) + 1);
+            if y_blocks
+                .clone()
+                .all(|y| x_blocks.clone().all(|x| inside[y][x]))
+            {
+                part2 = part2.max(area);
+            }
+        }
+    }
+
+    (part1 as usize, part2 as usize)
+}
@@ -0,0 +1,81 @@
+use shared::Rng;
+
+// The light solver explores sequences of at most 9 button presses
+const MAX_LIGHT_PRESSES: usize = 9;
+
+/// Generate `size` machines of 3 to 10 lights and 2 to 13 buttons. The light diagram is reached by
+/// pressing at most 9 buttons, and joltages are reached by pressing each button up to 20 times.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+    for _ in 0..size {
+        let light_count = rng.between(3, 10);
+        let button_count = rng.between(2, 12).min((1 << light_count) - 1);
+
+        // Buttons are all different, and wired to at least one light
+        let mut buttons: Vec<Vec<bool>> = vec![];
+        while buttons.len() < button_count {
+            let mut button: Vec<bool> = (0..light_count).map(|_| rng.chance(0.4)).collect();
+            button[rng.between(0, light_count - 1)] = true;
+            if !buttons.contains(&button) {
+                buttons.push(button);
+            }
+        }
+
+        // Every light is wired to at least one button (an extra button is added for the lights
+        // which are not wired yet)
+        let unwired: Vec<bool> = (0..light_count)
+            .map(|light| !buttons.iter().any(|button| button[light]))
+            .collect();
+        if unwired.contains(&true) {
+            buttons.push(unwired);
+        }
+
+        let mut pressed: Vec<usize> = (0..buttons.len()).collect();
+        rng.shuffle(&mut pressed);
+        pressed.truncate(rng.between(1, buttons.len().min(MAX_LIGHT_PRESSES)));
+
+        let mut lights = vec![false; light_count];
+        for button_index in &pressed {
+            for (light, wired) in lights.iter_mut().zip(&buttons[*button_index]) {
+                *light ^= wired;
+            }
+        }
+
+        let mut joltages = vec![0; light_count];
+        for button in &buttons {
+            let press_count = rng.between(0, 20);
+            for (joltage, wired) in joltages.iter_mut().zip(button) {
+                if *wired {
+                    *joltage += press_count;
+                }
+            }
+        }
+
+        let lights: String = lights
+            .iter()
+            .map(|light| if *light { '#' } else { '.' })
+            .collect();
+
+        let buttons: Vec<String> = buttons
+            .iter()
+            .map(|button| {
+                let wired: Vec<String> = (0..light_count)
+                    .filter(|light| button[*light])
+                    .map(|light| light.to_string())
+                    .collect();
+                format!("({})", wired.join(","))
+            })
+            .collect();
+
+        let joltages: Vec<String> = joltages.iter().map(|joltage| joltage.to_string()).collect();
+
+        output.push_str(&format!(
+            "[{}] {} {{{}}}\n",
+            lights,
+            buttons.join(" "),
+            joltages.join(",")
+        ));
+    }
+
+    output
+}
@@ -1,6 +1,40 @@
+mod generator;
+#[cfg(test)]
+mod reference;
+
 use microlp::{ComparisonOp, LinearExpr, OptimizationDirection, Problem};
-use regex::Regex;
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::parse::{ParseError, Parser};
+use shared::random;
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+// Lights are stored as the bits of an usize
+const MAX_LIGHT_COUNT: usize = usize::BITS as usize;
+
+// Larger joltages would not fit in the press count bounds of the solver
+const MAX_JOLTAGE: usize = 1_000_000;
+
+/// Error raised when no button presses give the expected state of a machine
+#[derive(Debug)]
+struct NoSolutionError {
+    machine_index: usize,
+}
+
+impl fmt::Display for NoSolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no solution found for machine {}",
+            self.machine_index + 1
+        )
+    }
+}
+
+impl Error for NoSolutionError {}
 
 struct JoltageMachine {
     expected_joltage: Vec<usize>,
@@ -38,10 +72,10 @@ impl LightMachine {
         }
 
         // Too many steps already
-        if let Some(best) = context.best_step_count {
-            if context.step >= *best {
-                return;
-            }
+        if let Some(best) = context.best_step_count
+            && context.step >= *best
+        {
+            return;
         }
 
         context.state_cost.insert(context.light_state, context.step);
@@ -66,7 +100,7 @@ impl LightMachine {
         }
     }
 
-    fn solve(&self) -> usize {
+    fn solve(&self) -> Option<usize> {
         let mut best_step_count = None;
         let mut state_cost = HashMap::new();
         self.solve_rec(LightContext {
@@ -76,84 +110,93 @@ impl LightMachine {
             state_cost: &mut state_cost,
         });
 
-        if let Some(best_step_count) = best_step_count {
-            return best_step_count;
+        best_step_count
+    }
+}
+
+fn parse_machine(line: &mut Parser) -> Result<(LightMachine, JoltageMachine), ParseError> {
+    let mut lights = line.delimited('[', ']')?;
+    let mut expected_lights = 0;
+    let mut light_count = 0;
+    for index in 0.. {
+        if index == MAX_LIGHT_COUNT && lights.peek().is_some() {
+            return Err(lights.error(format!("more than {} lights", MAX_LIGHT_COUNT)));
+        }
+
+        match lights.peek() {
+            Some('.') => {}
+            Some('#') => expected_lights |= 1 << index,
+            Some(_) => return Err(lights.error("expected `.` or `#`")),
+            None => break,
         }
 
-        panic!("No solution found");
+        lights.next_char()?;
+        light_count += 1;
+    }
+
+    if light_count == 0 {
+        return Err(lights.error("expected at least one light"));
+    }
+
+    let light_index = |p: &mut Parser| p.bounded(0..=light_count - 1);
+
+    let mut buttons: Vec<Vec<usize>> = vec![];
+    line.skip_spaces();
+    while line.peek() == Some('(') {
+        let mut button = line.delimited('(', ')')?;
+        buttons.push(button.separated(",", light_index)?);
+        button.end()?;
+        line.skip_spaces();
+    }
+
+    let mut joltages = line.delimited('{', '}')?;
+    let expected_joltage = joltages.separated(",", |p| p.bounded(0..=MAX_JOLTAGE))?;
+    joltages.end()?;
+    if expected_joltage.len() != light_count {
+        return Err(joltages.error(format!(
+            "expected {} joltages like lights, found {}",
+            light_count,
+            expected_joltage.len()
+        )));
     }
-}
 
-fn parse_input(raw_data: &str) -> (Vec<LightMachine>, Vec<JoltageMachine>) {
-    // I'm lazy...
-    let lights_re = Regex::new(r"\[(.*?)\]").unwrap();
-    let buttons_re = Regex::new(r"\((.*?)\)").unwrap();
-    let joltages_re = Regex::new(r"\{(.*?)\}").unwrap();
+    line.end()?;
+
+    Ok((
+        LightMachine {
+            expected_lights,
+            buttons: buttons
+                .iter()
+                .map(|button| button.iter().fold(0, |mask, index| mask | 1 << *index))
+                .collect(),
+        },
+        JoltageMachine {
+            expected_joltage,
+            buttons,
+        },
+    ))
+}
 
-    raw_data
+fn parse_input(raw_data: &str) -> Result<(Vec<LightMachine>, Vec<JoltageMachine>), ParseError> {
+    Parser::new(raw_data)
         .lines()
-        .map(|line| {
-            let capture = lights_re.captures_iter(line).next().unwrap();
-            let expected_lights: usize = capture[1]
-                .chars()
-                .enumerate()
-                .map(|(index, c)| match c {
-                    '.' => 0,
-                    '#' => 1 << index,
-                    _ => panic!("Unexpected character in lights"),
-                })
-                .sum();
-
-            let buttons: Vec<Vec<usize>> = buttons_re
-                .captures_iter(line)
-                .map(|cap| {
-                    cap[1]
-                        .split(',')
-                        .map(|s| s.trim().parse().unwrap())
-                        .collect::<Vec<usize>>()
-                })
-                .collect();
-
-            let joltages: Vec<usize> = joltages_re
-                .captures_iter(line)
-                .flat_map(|cap| {
-                    cap[1]
-                        .split(',')
-                        .map(|s| s.trim().parse().unwrap())
-                        .collect::<Vec<usize>>()
-                })
-                .collect();
-
-            (
-                LightMachine {
-                    expected_lights,
-                    buttons: buttons
-                        .iter()
-                        .map(|button| button.iter().map(|index| 1 << *index).sum())
-                        .collect(),
-                },
-                JoltageMachine {
-                    expected_joltage: joltages,
-                    buttons,
-                },
-            )
-        })
+        .map(|mut line| parse_machine(&mut line))
         .collect()
 }
 
-fn solve_part1(machines: &Vec<LightMachine>) -> usize {
+fn solve_part1(machines: &[LightMachine]) -> Result<usize, NoSolutionError> {
     let mut total = 0;
-    for machine in machines.iter() {
-        total += machine.solve();
+    for (machine_index, machine) in machines.iter().enumerate() {
+        total += machine.solve().ok_or(NoSolutionError { machine_index })?;
     }
 
-    total
+    Ok(total)
 }
 
-fn solve_part2(machines: &Vec<JoltageMachine>) -> usize {
+fn solve_part2(machines: &[JoltageMachine]) -> Result<usize, NoSolutionError> {
     let mut total = 0;
 
-    for machine in machines.iter() {
+    for (machine_index, machine) in machines.iter().enumerate() {
         let mut problem = Problem::new(OptimizationDirection::Minimize);
 
         // Add variables representing button presses
@@ -169,7 +212,7 @@ fn solve_part2(machines: &Vec<JoltageMachine>) -> usize {
                             .iter()
                             .map(|index| machine.expected_joltage[*index] as i32)
                             .min()
-                            .unwrap(),
+                            .unwrap_or(0),
                     ),
                 ),
             );
@@ -189,31 +232,57 @@ fn solve_part2(machines: &Vec<JoltageMachine>) -> usize {
         }
 
         // Solve the "press count" problem
-        let solution = problem.solve().unwrap();
+        let solution = problem
+            .solve()
+            .map_err(|_| NoSolutionError { machine_index })?;
         total += solution.objective().round() as usize;
     }
 
-    total
+    Ok(total)
+}
+
+fn fuzz_target(raw_data: &str) -> Result<(usize, usize), Box<dyn Error>> {
+    let (light_machines, joltage_machines) = parse_input(raw_data)?;
+    Ok((
+        solve_part1(&light_machines)?,
+        solve_part2(&joltage_machines)?,
+    ))
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-10.txt");
-    let (light_machines, joltage_machines) = parse_input(raw_data);
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-10.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let (light_machines, joltage_machines) = parse_input(&raw_data)?;
 
     println!("Solver - day 10:");
 
     // Part 1
-    let result = solve_part1(&light_machines);
+    let result = solve_part1(&light_machines)?;
     println!("  Part 1 - Final code: {}", result);
 
     // Part 2
-    let result = solve_part2(&joltage_machines);
+    let result = solve_part2(&joltage_machines)?;
     println!("  Part 2 - Final code: {}", result);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
 [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
@@ -221,15 +290,66 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let (light_machines, _) = parse_input(RAW_INPUT);
-        let result = solve_part1(&light_machines);
+        let (light_machines, _) = parse_input(RAW_INPUT).unwrap();
+        let result = solve_part1(&light_machines).unwrap();
         assert_eq!(result, 7);
     }
 
     #[test]
     fn check_part2_result() {
-        let (_, joltage_machines) = parse_input(RAW_INPUT);
-        let result = solve_part2(&joltage_machines);
+        let (_, joltage_machines) = parse_input(RAW_INPUT).unwrap();
+        let result = solve_part2(&joltage_machines).unwrap();
         assert_eq!(result, 33);
     }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..5 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 10);
+            let (light_machines, joltage_machines) = parse_input(&raw_data).unwrap();
+            assert_eq!((light_machines.len(), joltage_machines.len()), (10, 10));
+            for machine in &joltage_machines {
+                let light_count = machine.expected_joltage.len();
+                assert!((3..=10).contains(&light_count));
+                assert!((2..=13).contains(&machine.buttons.len()));
+
+                // Every light is wired to at least one button
+                assert!(
+                    (0..light_count).all(|light| {
+                        machine.buttons.iter().any(|button| button.contains(&light))
+                    })
+                );
+            }
+
+            // Each machine is configured with at most 9 presses
+            assert!(solve_part1(&light_machines).unwrap() <= 9 * 10);
+            assert!(solve_part2(&joltage_machines).is_ok());
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            1..=3,
+            10,
+            |input| fuzz_target(input).unwrap(),
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 3);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, fuzz_target);
+
+        let error = parse_input("[.#] (0,2) {1,2}").err().unwrap();
+        assert_eq!(error.message, "integer `2` out of range 0..=1");
+        assert!(parse_input("[] (0) {}").is_err());
+
+        // The second light is never turned on
+        let (light_machines, joltage_machines) = parse_input("[.#] (0) {1,2}").unwrap();
+        assert!(solve_part1(&light_machines).is_err());
+        assert!(solve_part2(&joltage_machines).is_err());
+    }
 }
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+struct Machine {
+    lights: Vec<bool>,
+    buttons: Vec<Vec<usize>>,
+    joltages: Vec<usize>,
+}
+
+fn parse_machine(line: &str) -> Machine {
+    let groups: Vec<&str> = line.split(' ').collect();
+    let (lights, groups) = groups.split_first().unwrap();
+    let (joltages, buttons) = groups.split_last().unwrap();
+    let values = |group: &str| -> Vec<usize> {
+        group[1..group.len() - 1]
+            .split(',')
+            .map(|value| value.parse().unwrap())
+            .collect()
+    };
+
+    Machine {
+        lights: lights[1..lights.len() - 1]
+            .chars()
+            .map(|c| c == '#')
+            .collect(),
+        buttons: buttons.iter().map(|button| values(button)).collect(),
+        joltages: values(joltages),
+    }
+}
+
+/// Counters added by pressing once each button of a subset, for all subsets of buttons, along with
+/// the number of buttons pressed
+fn press_patterns(machine: &Machine) -> Vec<(usize, Vec<usize>)> {
+    (0..1_usize << machine.buttons.len())
+        .map(|subset| {
+            let mut counters = vec![0; machine.lights.len()];
+            for (index, button) in machine.buttons.iter().enumerate() {
+                if subset & (1 << index) != 0 {
+                    for light in button {
+                        counters[*light] += 1;
+                    }
+                }
+            }
+
+            (subset.count_ones() as usize, counters)
+        })
+        .collect()
+}
+
+/// Fewest presses to reach the joltages. Presses of each button are split into an optional odd
+/// press and pairs of presses: the odd presses must fix the parity of each counter, and the pairs
+/// then have to reach half of the remaining joltages.
+fn min_joltage_presses(
+    patterns: &[(usize, Vec<usize>)],
+    joltages: Vec<usize>,
+    cache: &mut HashMap<Vec<usize>, Option<usize>>,
+) -> Option<usize> {
+    if joltages.iter().all(|joltage| *joltage == 0) {
+        return Some(0);
+    }
+
+    if let Some(presses) = cache.get(&joltages) {
+        return *presses;
+    }
+
+    let mut best: Option<usize> = None;
+    for (press_count, counters) in patterns {
+        let fits = counters
+            .iter()
+            .zip(&joltages)
+            .all(|(counter, joltage)| counter <= joltage && (joltage - counter) % 2 == 0);
+        if !fits {
+            continue;
+        }
+
+        let half: Vec<usize> = counters
+            .iter()
+            .zip(&joltages)
+            .map(|(counter, joltage)| (joltage - counter) / 2)
+            .collect();
+        if let Some(presses) = min_joltage_presses(patterns, half, cache) {
+            let presses = press_count + 2 * presses;
+            best = Some(best.map_or(presses, |best| best.min(presses)));
+        }
+    }
+
+    cache.insert(joltages, best);
+    best
+}
+
+/// Brute force solver: lights are solved by trying every subset of buttons (pressing a button
+/// twice does nothing), joltages with an exhaustive search on the parity of presses
+pub fn solve(raw_data: &str) -> (usize, usize) {
+    let mut part1 = 0;
+    let mut part2 = 0;
+    for machine in raw_data.lines().map(parse_machine) {
+        let patterns = press_patterns(&machine);
+        part1 += patterns
+            .iter()
+            .filter(|(_, counters)| {
+                counters
+                    .iter()
+                    .zip(&machine.lights)
+                    .all(|(counter, light)| (counter % 2 == 1) == *light)
+            })
+            .map(|(press_count, _)| *press_count)
+            .min()
+            .unwrap();
+
+        part2 += min_joltage_presses(&patterns, machine.joltages, &mut HashMap::new()).unwrap();
+    }
+
+    (part1, part2)
+}
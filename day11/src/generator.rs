@@ -0,0 +1,47 @@
+use shared::Rng;
+use std::collections::HashSet;
+
+const SPECIAL_DEVICES: [&str; 4] = ["you", "svr", "dac", "fft"];
+
+// Names are made of 3 lowercase letters
+const MAX_NAME_COUNT: usize = 26 * 26 * 26 - SPECIAL_DEVICES.len() - 1;
+
+/// Generate a graph of `size` devices (plus `you`, `svr`, `dac`, `fft` and `out`) without cycles,
+/// each device having 1 to 3 outputs
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut names: HashSet<String> = HashSet::new();
+    while names.len() < size.min(MAX_NAME_COUNT) {
+        let name: String = (0..3)
+            .map(|_| char::from(b'a' + rng.between(0, 25) as u8))
+            .collect();
+
+        if !SPECIAL_DEVICES.contains(&name.as_str()) && name != "out" {
+            names.insert(name);
+        }
+    }
+
+    // Devices can only output to devices after them, "out" being the last one
+    let mut devices: Vec<String> = names.into_iter().collect();
+    devices.sort();
+    devices.extend(SPECIAL_DEVICES.iter().map(|name| name.to_string()));
+    rng.shuffle(&mut devices);
+    devices.push("out".to_string());
+
+    let mut lines: Vec<String> = (0..devices.len() - 1)
+        .map(|index| {
+            let output_count = rng.between(1, 3).min(devices.len() - 1 - index);
+            let mut outputs: Vec<&str> = vec![];
+            while outputs.len() < output_count {
+                let output = devices[rng.between(index + 1, devices.len() - 1)].as_str();
+                if !outputs.contains(&output) {
+                    outputs.push(output);
+                }
+            }
+
+            format!("{}: {}", devices[index], outputs.join(" "))
+        })
+        .collect();
+
+    rng.shuffle(&mut lines);
+    lines.join("\n") + "\n"
+}
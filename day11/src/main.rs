@@ -1,109 +1,264 @@
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::number::{self, Number, OverflowError, Precision, Solve};
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use shared::trace;
 use std::collections::HashMap;
+use std::error::Error;
 
 struct Device<'a> {
     name: &'a str,
     targets: Vec<&'a str>,
 }
 
-fn parse_input(raw_data: &str) -> Vec<Device<'_>> {
-    let mut devices: Vec<Device> = raw_data
-        .lines()
-        .map(|line| {
-            let name_target_split: Vec<&str> = line.split(": ").collect();
-            let source_device = name_target_split[0];
-            let target_devices = name_target_split[1].split(' ').collect::<Vec<&str>>();
-
-            Device {
-                name: source_device,
-                targets: target_devices,
-            }
-        })
+fn parse_name<'a>(line: &mut Parser<'a>) -> Result<&'a str, ParseError> {
+    let name = line.take_while(|c| c.is_ascii_alphanumeric());
+    if name.is_empty() {
+        return Err(line.error("expected a device name"));
+    }
+
+    Ok(name)
+}
+
+fn parse_input(raw_data: &str) -> Result<Vec<Device<'_>>, ParseError> {
+    let mut devices: Vec<Device> = vec![];
+    let mut device_lines: HashMap<&str, Parser> = HashMap::new();
+    let mut target_starts: Vec<Parser> = vec![];
+    for mut line in Parser::new(raw_data).lines() {
+        let name_start = line;
+        let name = parse_name(&mut line)?;
+        if name == "out" {
+            return Err(name_start.error("`out` is reserved for the output"));
+        }
+        if device_lines.insert(name, name_start).is_some() {
+            return Err(name_start.error(format!("device `{}` defined twice", name)));
+        }
+
+        line.literal(":")?;
+        let mut targets = vec![];
+        line.skip_spaces();
+        while !line.is_empty() {
+            target_starts.push(line);
+            targets.push(parse_name(&mut line)?);
+            line.skip_spaces();
+        }
+
+        devices.push(Device { name, targets });
+    }
+
+    // Check that all targets exist
+    for (target, start) in devices
+        .iter()
+        .flat_map(|device| &device.targets)
+        .zip(target_starts)
+    {
+        if *target != "out" && !device_lines.contains_key(target) {
+            return Err(start.error(format!("unknown device `{}`", target)));
+        }
+    }
+
+    // Check that there is no cycle reachable from the start devices, paths through it could not
+    // be counted (removing reachable devices without inputs until none is left)
+    let indices: HashMap<&str, usize> = devices
+        .iter()
+        .enumerate()
+        .map(|(index, device)| (device.name, index))
         .collect();
+    let targets = |index: usize| {
+        devices[index]
+            .targets
+            .iter()
+            .filter_map(|target| indices.get(target).copied())
+    };
+
+    let mut reachable = vec![false; devices.len()];
+    let mut to_visit: Vec<usize> = ["you", "svr"]
+        .iter()
+        .filter_map(|name| indices.get(name).copied())
+        .collect();
+    while let Some(index) = to_visit.pop() {
+        if !reachable[index] {
+            reachable[index] = true;
+            to_visit.extend(targets(index));
+        }
+    }
+
+    let mut input_counts = vec![0_usize; devices.len()];
+    for index in (0..devices.len()).filter(|&index| reachable[index]) {
+        for target in targets(index) {
+            input_counts[target] += 1;
+        }
+    }
+
+    let mut sources: Vec<usize> = (0..devices.len())
+        .filter(|&index| reachable[index] && input_counts[index] == 0)
+        .collect();
+    while let Some(index) = sources.pop() {
+        for target in targets(index) {
+            input_counts[target] -= 1;
+            if input_counts[target] == 0 {
+                sources.push(target);
+            }
+        }
+    }
+
+    if let Some(index) = input_counts.iter().position(|&count| count > 0) {
+        let name = devices[index].name;
+        return Err(
+            device_lines[name].error(format!("device `{}` is reachable from a cycle", name))
+        );
+    }
 
     devices.push(Device {
         name: "out",
         targets: vec![],
     });
 
-    devices
+    Ok(devices)
 }
 
-fn count_paths_rec(
+fn count_paths_rec<T: Number>(
     device_name: &str,
     target_device_name: &str,
-    devices: &Vec<Device>,
-    count_cache: &mut HashMap<String, usize>,
-) -> usize {
+    devices: &[Device],
+    count_cache: &mut HashMap<String, T>,
+) -> Result<T, OverflowError> {
     // Found a path!
     if device_name == target_device_name {
-        return 1;
+        return Ok(T::from_usize(1));
     }
 
     // Already processed
-    if let Some(&count) = count_cache.get(device_name) {
-        return count;
+    if let Some(count) = count_cache.get(device_name) {
+        return Ok(count.clone());
     }
 
     // Recursion over next devices
-    let device = devices.iter().find(|d| d.name == device_name).unwrap();
-    let mut count = 0;
+    // Unknown start device: there is no path from it
+    let Some(device) = devices.iter().find(|d| d.name == device_name) else {
+        return Ok(T::zero());
+    };
+    let mut count = T::zero();
     for next_device_name in &device.targets {
-        count += count_paths_rec(next_device_name, target_device_name, devices, count_cache);
+        let next_count =
+            count_paths_rec(next_device_name, target_device_name, devices, count_cache)?;
+        count = count.try_add(&next_count)?;
     }
 
-    count_cache.insert(device_name.to_string(), count);
-    count
+    trace::event(
+        "path_count",
+        &[
+            ("device", &device_name),
+            ("target", &target_device_name),
+            ("count", &count),
+        ],
+    );
+    count_cache.insert(device_name.to_string(), count.clone());
+    Ok(count)
 }
 
-fn count_paths(start_device_name: &str, path: &[&str], devices: &Vec<Device>) -> usize {
+fn count_paths<T: Number>(
+    start_device_name: &str,
+    path: &[&str],
+    devices: &[Device],
+) -> Result<T, OverflowError> {
     let mut current_device_name = start_device_name;
 
-    let mut total = 1;
+    let mut total = T::from_usize(1);
     for target_device_name in path {
-        let paths_count = count_paths_rec(
+        let paths_count: T = count_paths_rec(
             current_device_name,
             target_device_name,
             devices,
             &mut HashMap::new(),
-        );
-        if paths_count == 0 {
-            return 0;
+        )?;
+        if paths_count.is_zero() {
+            return Ok(T::zero());
         }
 
-        total *= paths_count;
+        total = total.try_mul(&paths_count)?;
         current_device_name = target_device_name;
     }
 
-    total
+    Ok(total)
 }
 
-fn solve_part1(devices: &Vec<Device>) -> usize {
+fn solve_part1<T: Number>(devices: &[Device]) -> Result<T, OverflowError> {
     count_paths_rec("you", "out", devices, &mut HashMap::new())
 }
 
-fn solve_part2(devices: &Vec<Device>) -> usize {
-    count_paths("svr", &["dac", "fft", "out"], devices)
-        + count_paths("svr", &["fft", "dac", "out"], devices)
+fn solve_part2<T: Number>(devices: &[Device]) -> Result<T, OverflowError> {
+    let count: T = count_paths("svr", &["dac", "fft", "out"], devices)?;
+    count.try_add(&count_paths("svr", &["fft", "dac", "out"], devices)?)
+}
+
+// Adapters used to select the integer type of the results at runtime
+struct Part1<'a>(&'a [Device<'a>]);
+struct Part2<'a>(&'a [Device<'a>]);
+
+impl Solve for Part1<'_> {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+        solve_part1(self.0)
+    }
+}
+
+impl Solve for Part2<'_> {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+        solve_part2(self.0)
+    }
+}
+
+fn fuzz_target(raw_data: &str) -> Result<(), Box<dyn Error>> {
+    let input = parse_input(raw_data)?;
+    solve_part1::<usize>(&input)?;
+    solve_part2::<usize>(&input)?;
+    Ok(())
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-11.txt");
-    let input = parse_input(raw_data);
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    trace::enable_from_args(&args);
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let precision = args.value_or("precision", Precision::Checked)?;
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-11.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let input = parse_input(&raw_data)?;
 
     println!("Solver - day 11:");
 
     // Part 1
-    let result = solve_part1(&input);
+    let result = number::solve_with(&Part1(&input), precision)?;
     println!("  Part 1 - Final code: {}", result);
 
     // Part 2
-    let result = solve_part2(&input);
+    let result = number::solve_with(&Part2(&input), precision)?;
     println!("  Part 2 - Final code: {}", result);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::BigUint;
+    use shared::Rng;
+    use shared::differential;
 
     #[test]
     fn check_part1_result() {
@@ -118,8 +273,8 @@ ggg: out
 hhh: ccc fff iii
 iii: out";
 
-        let input = parse_input(raw_data);
-        let result = solve_part1(&input);
+        let input = parse_input(raw_data).unwrap();
+        let result = solve_part1::<usize>(&input).unwrap();
         assert_eq!(result, 5);
     }
 
@@ -139,8 +294,112 @@ fff: ggg hhh
 ggg: out
 hhh: out";
 
-        let input = parse_input(raw_data);
-        let result = solve_part2(&input);
+        let input = parse_input(raw_data).unwrap();
+        let result = solve_part2::<usize>(&input).unwrap();
         assert_eq!(result, 2);
     }
+
+    #[test]
+    fn check_big_result() {
+        // Chain of 130 diamonds, each one doubling the number of paths
+        let mut raw_data = String::new();
+        for index in 0..130 {
+            raw_data.push_str(&format!("n{index}: a{index} b{index}\n"));
+            raw_data.push_str(&format!(
+                "a{index}: n{}\nb{index}: n{}\n",
+                index + 1,
+                index + 1
+            ));
+        }
+        raw_data.push_str("you: n0\nn130: out");
+
+        let input = parse_input(&raw_data).unwrap();
+        assert!(solve_part1::<u128>(&input).is_err());
+
+        let result = solve_part1::<BigUint>(&input).unwrap();
+        assert_eq!(
+            result.to_string(),
+            "1361129467683753853853498429727072845824"
+        );
+    }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 50);
+            let input = parse_input(&raw_data).unwrap();
+
+            // All devices but `out` have 1 to 3 outputs
+            assert_eq!(input.len(), 50 + 5);
+            for name in ["you", "svr", "dac", "fft", "out"] {
+                assert!(input.iter().any(|device| device.name == name));
+            }
+            for device in &input {
+                let output_counts = if device.name == "out" { 0..=0 } else { 1..=3 };
+                assert!(output_counts.contains(&device.targets.len()));
+            }
+            assert!(solve_part1::<BigUint>(&input).is_ok());
+            assert!(solve_part2::<BigUint>(&input).is_ok());
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        differential::assert_agree(
+            generator::generate,
+            0..=12,
+            20,
+            |input| {
+                let input = parse_input(input).unwrap();
+                (
+                    solve_part1::<u128>(&input).unwrap(),
+                    solve_part2::<u128>(&input).unwrap(),
+                )
+            },
+            reference::solve,
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 5);
+        fuzz::assert_no_crash(&[&generated], 2000, fuzz_target);
+
+        let error = parse_input("you: aaa\naaa: bbb").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 6));
+        assert_eq!(error.message, "unknown device `bbb`");
+
+        let error = parse_input("you: aaa\naaa: bbb\nbbb: aaa out")
+            .err()
+            .unwrap();
+        assert!(error.message.ends_with("is reachable from a cycle"));
+
+        // Cycles which cannot be reached from `you` or `svr` do not prevent counting paths
+        let input = parse_input("you: out\naaa: bbb\nbbb: aaa you").unwrap();
+        assert_eq!(solve_part1::<usize>(&input), Ok(1));
+        assert_eq!(solve_part2::<usize>(&input), Ok(0));
+
+        let error = parse_input("you: out\nout: you").err().unwrap();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.message, "`out` is reserved for the output");
+
+        // Without `svr`, there is no path for part 2
+        let input = parse_input("you: out").unwrap();
+        assert_eq!(solve_part2::<usize>(&input), Ok(0));
+    }
+
+    #[test]
+    fn check_trace() {
+        let input = parse_input("you: aaa bbb\naaa: bbb out\nbbb: out").unwrap();
+        let (_, events) = trace::capture(|| solve_part1::<usize>(&input));
+        let events: Vec<String> = events.iter().map(|event| event.to_string()).collect();
+        assert_eq!(
+            events,
+            [
+                "path_count device=bbb target=out count=1",
+                "path_count device=aaa target=out count=2",
+                "path_count device=you target=out count=3"
+            ]
+        );
+    }
 }
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+
+/// Count paths to `out` by walking each one of them (without any caching), and the ones visiting
+/// both `dac` and `fft`
+fn walk(
+    device: &str,
+    outputs: &HashMap<&str, Vec<&str>>,
+    visited_dac: bool,
+    visited_fft: bool,
+) -> (u128, u128) {
+    let visited_dac = visited_dac || device == "dac";
+    let visited_fft = visited_fft || device == "fft";
+    if device == "out" {
+        return (1, (visited_dac && visited_fft) as u128);
+    }
+
+    let mut counts = (0, 0);
+    for output in &outputs[device] {
+        let (path_count, problem_path_count) = walk(output, outputs, visited_dac, visited_fft);
+        counts.0 += path_count;
+        counts.1 += problem_path_count;
+    }
+
+    counts
+}
+
+/// Brute force solver enumerating all paths one by one
+pub fn solve(raw_data: &str) -> (u128, u128) {
+    let outputs: HashMap<&str, Vec<&str>> = raw_data
+        .lines()
+        .map(|line| {
+            let (device, outputs) = line.split_once(": ").unwrap();
+            (device, outputs.split(' ').collect())
+        })
+        .collect();
+
+    (
+        walk("you", &outputs, false, false).0,
+        walk("svr", &outputs, false, false).1,
+    )
+}
@@ -0,0 +1,51 @@
+use shared::Rng;
+
+const SHAPE_COUNT: usize = 6;
+
+/// Generate 6 shapes of 5 to 8 cells followed by `size` regions. Regions either fit their
+/// presents without interlocking them (one present per 3x3 block), or are too small for them.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+
+    let mut cell_counts = vec![];
+    for id in 0..SHAPE_COUNT {
+        let mut cells = [false; 9];
+        let cell_count = rng.between(5, 8);
+        for cell in cells.iter_mut().take(cell_count) {
+            *cell = true;
+        }
+        rng.shuffle(&mut cells);
+        cell_counts.push(cell_count);
+
+        output.push_str(&format!("{}:\n", id));
+        for row in cells.chunks(3) {
+            let row: String = row.iter().map(|c| if *c { '#' } else { '.' }).collect();
+            output.push_str(&row);
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    for _ in 0..size {
+        let width = rng.between(12, 50);
+        let height = rng.between(12, 50);
+
+        let present_count = if rng.chance(0.5) {
+            rng.between(0, (width / 3) * (height / 3))
+        } else {
+            // More presents than cells, even with the smallest shape
+            let min_cell_count = cell_counts.iter().min().unwrap();
+            width * height / min_cell_count + rng.between(1, 10)
+        };
+
+        let mut counts = [0; SHAPE_COUNT];
+        for _ in 0..present_count {
+            counts[rng.between(0, SHAPE_COUNT - 1)] += 1;
+        }
+
+        let counts: Vec<String> = counts.iter().map(|count| count.to_string()).collect();
+        output.push_str(&format!("{}x{}: {}\n", width, height, counts.join(" ")));
+    }
+
+    output
+}
@@ -1,5 +1,15 @@
-use shared::{Grid, Vector};
+mod generator;
+#[cfg(test)]
+mod reference;
+
+use shared::args::Args;
+use shared::fuzz;
+use shared::input::{self, TrailingWhitespace};
+use shared::parse::{ParseError, Parser};
+use shared::random;
+use shared::{BitGrid, Vector};
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
 
 struct Region {
     size: Vector,
@@ -8,21 +18,18 @@ struct Region {
 
 const SHAPE_SIZE: Vector = Vector { x: 3, y: 3 };
 
+// Bounds keeping areas and occupied cell counts far from overflows
+const MAX_REGION_SIZE: i64 = 100_000;
+const MAX_PRESENT_COUNT: usize = 1_000_000;
+
 struct Shape {
-    occupied_count: usize,       // Number of occupied cells
-    shapes: HashSet<Grid<bool>>, // All combinations (rotated/flipped)
+    occupied_count: usize,    // Number of occupied cells
+    shapes: HashSet<BitGrid>, // All combinations (rotated/flipped)
 }
 
 impl Shape {
-    fn new(shape: Grid<bool>) -> Self {
-        let mut occupied_count = 0;
-        for y in 0..SHAPE_SIZE.y {
-            for x in 0..SHAPE_SIZE.x {
-                if shape[Vector { x, y }] {
-                    occupied_count += 1;
-                }
-            }
-        }
+    fn new(shape: BitGrid) -> Self {
+        let occupied_count = shape.count_ones();
 
         // Rotate and flip to get all possible variants
         let mut variants = HashSet::new();
@@ -42,37 +49,37 @@ impl Shape {
         }
     }
 
-    fn flip(shape: &Grid<bool>) -> Grid<bool> {
-        let mut new_shape = Grid::with_capacity(SHAPE_SIZE, false);
+    fn flip(shape: &BitGrid) -> BitGrid {
+        let mut new_shape = BitGrid::new(SHAPE_SIZE);
 
         // Flip on Y axis
         for y in 0..SHAPE_SIZE.y {
             for x in 0..SHAPE_SIZE.x {
-                let value = shape[Vector { x, y }];
+                let value = shape.get(&Vector { x, y }).unwrap();
                 let new_pos = Vector {
                     x: SHAPE_SIZE.x - 1 - x,
                     y,
                 };
 
-                new_shape[new_pos] = value;
+                new_shape.set(&new_pos, value);
             }
         }
 
         new_shape
     }
 
-    fn rotate(shape: &Grid<bool>) -> Grid<bool> {
-        let mut new_shape = Grid::with_capacity(SHAPE_SIZE, false);
+    fn rotate(shape: &BitGrid) -> BitGrid {
+        let mut new_shape = BitGrid::new(SHAPE_SIZE);
 
         for y in 0..SHAPE_SIZE.y {
             for x in 0..SHAPE_SIZE.x {
-                let value = shape[Vector { x, y }];
+                let value = shape.get(&Vector { x, y }).unwrap();
                 let new_pos = Vector {
                     x: SHAPE_SIZE.y - 1 - y,
                     y: x,
                 };
 
-                new_shape[new_pos] = value;
+                new_shape.set(&new_pos, value);
             }
         }
 
@@ -80,78 +87,105 @@ impl Shape {
     }
 }
 
-fn parse_input(raw_data: &str) -> (Vec<Region>, HashMap<usize, Shape>) {
-    let shapes_re = regex::Regex::new(r"(\d+):\n((?:[.#]+\n?)+)").unwrap();
-    let regions_re = regex::Regex::new(r"(\d+x\d+): ((?:\d+ )+\d+)").unwrap();
-
-    let shapes_by_id: HashMap<usize, Shape> = shapes_re
-        .captures_iter(raw_data)
-        .map(|cap| {
-            let id: usize = cap[1].parse().unwrap();
-            let shape_lines: Vec<&str> = cap[2].trim().lines().collect();
-            let mut grid = Grid::with_capacity(SHAPE_SIZE, false);
-            for (y, line) in shape_lines.iter().enumerate() {
-                for (x, ch) in line.chars().enumerate() {
-                    let pos = Vector {
-                        x: x as i64,
-                        y: y as i64,
-                    };
-                    grid[pos] = ch == '#';
-                }
-            }
-
-            (id, Shape::new(grid))
-        })
-        .collect();
-
-    let regions = regions_re
-        .captures_iter(raw_data)
-        .map(|cap| {
-            let parts: Vec<&str> = cap[1].split('x').collect();
-            let region_size = Vector {
-                x: parts[0].parse().unwrap(),
-                y: parts[1].parse().unwrap(),
+fn parse_shape(section: &Parser) -> Result<(usize, Shape), ParseError> {
+    let mut lines = section.lines();
+    let mut header = lines.next().unwrap();
+    let id = header.unsigned()?;
+    header.literal(":")?;
+    header.end()?;
+
+    let mut grid = BitGrid::new(SHAPE_SIZE);
+    for (y, mut line) in lines.enumerate() {
+        for x in 0.. {
+            let occupied = match line.peek() {
+                Some('#') => true,
+                Some('.') => false,
+                Some(_) => return Err(line.error("expected `.` or `#`")),
+                None => break,
             };
 
-            let shape_ids: HashMap<usize, usize> = cap[2]
-                .trim()
-                .split_whitespace()
-                .enumerate()
-                .map(|(index, s)| (index, s.parse().unwrap()))
-                .filter(|(_, count)| *count > 0)
-                .collect();
-
-            Region {
-                size: region_size,
-                shape_count_by_id: shape_ids,
+            let pos = Vector { x, y: y as i64 };
+            if !grid.set(&pos, occupied) {
+                return Err(line.error("shape larger than 3x3"));
             }
-        })
-        .collect();
 
-    (regions, shapes_by_id)
+            line.next_char()?;
+        }
+    }
+
+    Ok((id, Shape::new(grid)))
 }
 
-fn try_put_shape(pos: &Vector, shape: &Grid<bool>, grid: &Grid<bool>) -> Option<Grid<bool>> {
-    let mut next_grid = grid.clone();
-    for x in 0..SHAPE_SIZE.x {
-        for y in 0..SHAPE_SIZE.y {
-            let relative_pos = Vector { x, y };
-            let grid_pos = *pos + relative_pos;
-            let grid_occupied = next_grid[grid_pos];
-            let shape_occupied = shape[relative_pos];
-
-            if shape_occupied {
-                // Already occupied in the grid
-                if grid_occupied {
-                    return None;
-                }
+fn parse_region(
+    line: &mut Parser,
+    shapes_by_id: &HashMap<usize, Shape>,
+) -> Result<Region, ParseError> {
+    let x = line.bounded(0..=MAX_REGION_SIZE)?;
+    line.literal("x")?;
+    let y = line.bounded(0..=MAX_REGION_SIZE)?;
+    line.literal(": ")?;
+    let shape_counts = line.separated(" ", |p| p.bounded(0..=MAX_PRESENT_COUNT))?;
+    line.end()?;
+
+    if let Some((id, _)) = shape_counts
+        .iter()
+        .enumerate()
+        .find(|(id, count)| **count > 0 && !shapes_by_id.contains_key(id))
+    {
+        return Err(line.error(format!("presents of unknown shape {}", id)));
+    }
 
-                // Mark position as occupied
-                next_grid[grid_pos] = true;
-            }
+    Ok(Region {
+        size: Vector { x, y },
+        shape_count_by_id: shape_counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, count)| *count > 0)
+            .collect(),
+    })
+}
+
+fn parse_input(raw_data: &str) -> Result<(Vec<Region>, HashMap<usize, Shape>), ParseError> {
+    let mut regions = vec![];
+    let mut shapes_by_id = HashMap::new();
+
+    // Sections are either a single shape, or a list of regions (parsed once all shapes are known)
+    let mut region_sections = vec![];
+    for section in Parser::new(raw_data).sections() {
+        let mut first_line = section.lines().next().unwrap();
+        first_line.take_while(|c| c.is_ascii_digit());
+        if first_line.peek() == Some('x') {
+            region_sections.push(section);
+        } else {
+            let (id, shape) = parse_shape(&section)?;
+            shapes_by_id.insert(id, shape);
+        }
+    }
+
+    for section in region_sections {
+        for mut line in section.lines() {
+            regions.push(parse_region(&mut line, &shapes_by_id)?);
         }
     }
 
+    Ok((regions, shapes_by_id))
+}
+
+fn try_put_shape(pos: &Vector, shape: &BitGrid, grid: &BitGrid) -> Option<BitGrid> {
+    // Already occupied in the grid
+    if grid.overlaps(shape, pos) {
+        return None;
+    }
+
+    // Mark positions as occupied
+    let mut next_grid = grid.clone();
+    next_grid.or_shifted(shape, pos);
+
+    // Parts of the shape outside the region are dropped, only empty cells can be outside
+    if next_grid.count_ones() != grid.count_ones() + shape.count_ones() {
+        return None;
+    }
+
     Some(next_grid)
 }
 
@@ -169,27 +203,30 @@ fn can_fit_in_region(region: &Region, shapes_by_id: &HashMap<usize, Shape>) -> b
     true
 }
 
-fn solve_part1_rec(shape_count_by_id: &HashMap<usize, usize>, shapes_by_id: &HashMap<usize, Shape>, grid: &Grid<bool>) -> bool
-{
+fn solve_part1_rec(
+    shape_count_by_id: &HashMap<usize, usize>,
+    shapes_by_id: &HashMap<usize, Shape>,
+    grid: &BitGrid,
+) -> bool {
     for (shape_id, shape_count) in shape_count_by_id {
-        let shape = &shapes_by_id[&shape_id];
-        if 0 == *shape_count
-        {
+        let shape = &shapes_by_id[shape_id];
+        if 0 == *shape_count {
             continue;
         }
 
-        for x in 0..grid.size().x - SHAPE_SIZE.x + 1 {
-            for y in 0..grid.size().y - SHAPE_SIZE.y + 1 {
+        // Shapes may stick out of the region by their empty cells
+        for x in 1 - SHAPE_SIZE.x..grid.size().x {
+            for y in 1 - SHAPE_SIZE.y..grid.size().y {
                 let pos = Vector { x, y };
 
                 for shape_variant in &shape.shapes {
                     if let Some(next_grid) = try_put_shape(&pos, shape_variant, grid) {
                         // Successfully placed shape, continue with next shape
                         let mut next_shape_count_by_id = shape_count_by_id.clone();
-                        let count = next_shape_count_by_id.get_mut(&shape_id).unwrap();
+                        let count = next_shape_count_by_id.get_mut(shape_id).unwrap();
                         *count -= 1;
                         if *count == 0 {
-                            next_shape_count_by_id.remove(&shape_id);
+                            next_shape_count_by_id.remove(shape_id);
                         }
 
                         if next_shape_count_by_id.is_empty() {
@@ -208,7 +245,11 @@ fn solve_part1_rec(shape_count_by_id: &HashMap<usize, usize>, shapes_by_id: &Has
     false
 }
 
-fn solve_part1(regions: &Vec<Region>, shapes_by_id: &HashMap<usize, Shape>, check_only_areas: bool) -> usize {
+fn solve_part1(
+    regions: &[Region],
+    shapes_by_id: &HashMap<usize, Shape>,
+    check_only_areas: bool,
+) -> usize {
     let mut total = 0;
 
     for region in regions {
@@ -220,9 +261,8 @@ fn solve_part1(regions: &Vec<Region>, shapes_by_id: &HashMap<usize, Shape>, chec
 
             // Try all combinations
             // This solution is killing my CPU even with the example input...
-            let grid = Grid::with_capacity(region.size, false);
-            if solve_part1_rec(&region.shape_count_by_id, shapes_by_id, &grid)
-            {
+            let grid = BitGrid::new(region.size);
+            if solve_part1_rec(&region.shape_count_by_id, shapes_by_id, &grid) {
                 total += 1;
             }
         }
@@ -235,9 +275,28 @@ fn solve_part2() -> usize {
     0
 }
 
-fn main() {
-    let raw_data = include_str!("../input/input-12.txt");
-    let (regions, shapes_by_id) = parse_input(raw_data);
+fn fuzz_target(raw_data: &str) -> Result<usize, ParseError> {
+    let (regions, shapes_by_id) = parse_input(raw_data)?;
+
+    // Trying all combinations is too slow for fuzzing
+    Ok(solve_part1(&regions, &shapes_by_id, true))
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::from_env();
+    if random::print_generated(&args, generator::generate)? {
+        return Ok(());
+    }
+
+    let raw_data = input::normalize(
+        include_str!("../input/input-12.txt"),
+        TrailingWhitespace::Trim,
+    );
+    if fuzz::run_from_args(&args, &raw_data, generator::generate, fuzz_target)? {
+        return Ok(());
+    }
+
+    let (regions, shapes_by_id) = parse_input(&raw_data)?;
 
     println!("Solver - day 12:");
 
@@ -248,11 +307,15 @@ fn main() {
     // Part 2
     let result = solve_part2();
     println!("  Part 2 - Final code: {}", result);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use shared::Rng;
+    use shared::differential;
 
     const RAW_INPUT: &str = "0:
 ###
@@ -290,16 +353,99 @@ mod tests {
 
     #[test]
     fn check_part1_result() {
-        let (regions, shapes_by_id) = parse_input(RAW_INPUT);
+        let (regions, shapes_by_id) = parse_input(RAW_INPUT).unwrap();
         let result = solve_part1(&regions, &shapes_by_id, true);
         assert_eq!(result, 3);
     }
 
+    #[test]
+    fn check_shape_sticking_out() {
+        // The empty row and column of the shape can be outside of the region
+        let raw_data = "0:\n##.\n##.\n...\n\n2x2: 1\n2x2: 2";
+        let (regions, shapes_by_id) = parse_input(raw_data).unwrap();
+        assert_eq!(solve_part1(&regions, &shapes_by_id, false), 1);
+        assert_eq!(reference::solve(raw_data), 1);
+    }
+
     // #[test]
     // fn check_part2_result() {
-    //     let input = parse_input(RAW_INPUT);
-    //     let input = parse_input(RAW_INPUT);
+    //     let input = parse_input(RAW_INPUT).unwrap();
+    //     let input = parse_input(RAW_INPUT).unwrap();
     //     let result = solve_part2(&input);
     //     assert_eq!(result, 2);
     // }
+
+    #[test]
+    fn check_generated_input() {
+        for seed in 0..10 {
+            let raw_data = generator::generate(&mut Rng::new(seed), 20);
+            let (regions, shapes_by_id) = parse_input(&raw_data).unwrap();
+            assert_eq!((regions.len(), shapes_by_id.len()), (20, 6));
+            assert!(
+                shapes_by_id
+                    .values()
+                    .all(|shape| (5..=8).contains(&shape.occupied_count))
+            );
+
+            // Regions either have a 3x3 block per present, or fewer cells than their presents
+            let mut fitting_count = 0;
+            for region in &regions {
+                assert!((12..=50).contains(&region.size.x) && (12..=50).contains(&region.size.y));
+                let present_count: usize = region.shape_count_by_id.values().sum();
+                let block_count = (region.size.x / 3 * (region.size.y / 3)) as usize;
+                if present_count <= block_count {
+                    fitting_count += 1;
+                } else {
+                    let cell_count: usize = region
+                        .shape_count_by_id
+                        .iter()
+                        .map(|(id, count)| shapes_by_id[id].occupied_count * count)
+                        .sum();
+                    assert!(cell_count > (region.size.x * region.size.y) as usize);
+                }
+            }
+            assert_eq!(solve_part1(&regions, &shapes_by_id, true), fitting_count);
+        }
+    }
+
+    #[test]
+    fn check_against_reference() {
+        let solve = |check_only_areas| {
+            move |input: &str| {
+                let (regions, shapes_by_id) = parse_input(input).unwrap();
+                solve_part1(&regions, &shapes_by_id, check_only_areas)
+            }
+        };
+
+        differential::assert_agree(
+            reference::generate_small,
+            1..=3,
+            10,
+            solve(false),
+            reference::solve,
+        );
+
+        // Checking areas only is not enough for small regions, where presents have to interlock
+        assert!(
+            differential::find_disagreement(
+                reference::generate_small,
+                1..=3,
+                10,
+                solve(true),
+                reference::solve,
+            )
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn check_fuzzed_input() {
+        let generated = generator::generate(&mut Rng::new(0), 5);
+        fuzz::assert_no_crash(&[RAW_INPUT, &generated], 2000, fuzz_target);
+
+        // Regions may come before shapes
+        assert_eq!(fuzz_target("4x4: 1\n\n0:\n###").unwrap(), 1);
+        let error = fuzz_target("0:\n###\n\n4x4: 1 0 2").unwrap_err();
+        assert_eq!(error.message, "presents of unknown shape 2");
+    }
 }
@@ -0,0 +1,172 @@
+use crate::generator;
+use shared::Rng;
+
+type Cells = Vec<(usize, usize)>;
+
+/// All rotations and flips of a shape, as lists of cells sorted by row then column
+fn variants(shape: &[Vec<bool>]) -> Vec<Cells> {
+    let mut variants: Vec<Cells> = vec![];
+    for transform in 0..8 {
+        let mut cells: Cells = vec![];
+        for (y, row) in shape.iter().enumerate() {
+            for (x, occupied) in row.iter().enumerate() {
+                if *occupied {
+                    let (x, y) = if transform & 4 != 0 {
+                        (2 - x, y)
+                    } else {
+                        (x, y)
+                    };
+                    let (x, y) = match transform & 3 {
+                        0 => (x, y),
+                        1 => (2 - y, x),
+                        2 => (2 - x, 2 - y),
+                        _ => (y, 2 - x),
+                    };
+                    cells.push((y, x));
+                }
+            }
+        }
+
+        cells.sort();
+        let cells = cells.into_iter().map(|(y, x)| (x, y)).collect();
+        if !variants.contains(&cells) {
+            variants.push(cells);
+        }
+    }
+
+    variants
+}
+
+/// Exhaustive search filling the region cell by cell: the first free cell is either left empty
+/// (while some cells can still be left empty), or is the first cell of a present
+fn fill(
+    grid: &mut Vec<Vec<bool>>,
+    counts: &mut [usize],
+    shapes: &[Vec<Cells>],
+    empty_budget: usize,
+) -> bool {
+    let Some((x, y)) = (0..grid.len())
+        .flat_map(|y| (0..grid[y].len()).map(move |x| (x, y)))
+        .find(|(x, y)| !grid[*y][*x])
+    else {
+        return counts.iter().all(|count| *count == 0);
+    };
+
+    if counts.iter().all(|count| *count == 0) {
+        return true;
+    }
+
+    for shape_id in 0..shapes.len() {
+        if counts[shape_id] == 0 {
+            continue;
+        }
+
+        for variant in &shapes[shape_id] {
+            // Anchor the first cell of the variant on the free cell
+            let (first_x, first_y) = variant[0];
+            let cells: Option<Cells> = variant
+                .iter()
+                .map(|(cell_x, cell_y)| {
+                    let cell = ((x + cell_x).checked_sub(first_x)?, y + cell_y - first_y);
+                    let free = grid.get(cell.1)?.get(cell.0) == Some(&false);
+                    free.then_some(cell)
+                })
+                .collect();
+
+            let Some(cells) = cells else {
+                continue;
+            };
+
+            for (cell_x, cell_y) in &cells {
+                grid[*cell_y][*cell_x] = true;
+            }
+            counts[shape_id] -= 1;
+
+            let filled = fill(grid, counts, shapes, empty_budget);
+
+            counts[shape_id] += 1;
+            for (cell_x, cell_y) in &cells {
+                grid[*cell_y][*cell_x] = false;
+            }
+
+            if filled {
+                return true;
+            }
+        }
+    }
+
+    if empty_budget == 0 {
+        return false;
+    }
+
+    grid[y][x] = true;
+    let filled = fill(grid, counts, shapes, empty_budget - 1);
+    grid[y][x] = false;
+    filled
+}
+
+/// Brute force solver trying to place the presents of each region
+pub fn solve(raw_data: &str) -> usize {
+    let sections: Vec<&str> = raw_data.trim_end().split("\n\n").collect();
+    let (regions, shapes) = sections.split_last().unwrap();
+
+    let shapes: Vec<Vec<Vec<bool>>> = shapes
+        .iter()
+        .map(|shape| {
+            shape
+                .lines()
+                .skip(1)
+                .map(|line| line.chars().map(|c| c == '#').collect())
+                .collect()
+        })
+        .collect();
+    let cell_counts: Vec<usize> = shapes
+        .iter()
+        .map(|shape| shape.concat().iter().filter(|c| **c).count())
+        .collect();
+    let shapes: Vec<Vec<Cells>> = shapes.iter().map(|shape| variants(shape)).collect();
+
+    regions
+        .lines()
+        .filter(|region| {
+            let (size, counts) = region.split_once(": ").unwrap();
+            let (width, height) = size.split_once('x').unwrap();
+            let (width, height): (usize, usize) = (width.parse().unwrap(), height.parse().unwrap());
+            let mut counts: Vec<usize> = counts
+                .split(' ')
+                .map(|count| count.parse().unwrap())
+                .collect();
+
+            let occupied: usize = counts
+                .iter()
+                .zip(&cell_counts)
+                .map(|(count, cells)| count * cells)
+                .sum();
+            if occupied > width * height {
+                return false;
+            }
+
+            let mut grid = vec![vec![false; width]; height];
+            fill(&mut grid, &mut counts, &shapes, width * height - occupied)
+        })
+        .count()
+}
+
+/// Generate the shapes of a puzzle input, followed by `size` small regions with a few presents
+pub fn generate_small(rng: &mut Rng, size: usize) -> String {
+    let mut output = generator::generate(rng, 0);
+    for _ in 0..size {
+        let width = rng.between(3, 5);
+        let height = rng.between(3, 5);
+
+        let mut counts = [0; 6];
+        for _ in 0..rng.between(1, 3) {
+            counts[rng.between(0, 5)] += 1;
+        }
+
+        let counts: Vec<String> = counts.iter().map(|count| count.to_string()).collect();
+        output.push_str(&format!("{}x{}: {}\n", width, height, counts.join(" ")));
+    }
+
+    output
+}
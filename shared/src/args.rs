@@ -0,0 +1,94 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Error raised when a command line argument is invalid
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ArgsError {
+    pub message: String,
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+/// Command line arguments of a solver: options are given as `--name` or `--name=value`, all other
+/// arguments are positional
+pub struct Args {
+    values: Vec<String>,
+}
+
+impl Args {
+    pub fn new(values: impl IntoIterator<Item = impl Into<String>>) -> Args {
+        Args {
+            values: values.into_iter().map(|value| value.into()).collect(),
+        }
+    }
+
+    /// Arguments of the current process, without the program name
+    pub fn from_env() -> Args {
+        Self::new(std::env::args().skip(1))
+    }
+
+    /// Check if `--name` is present
+    pub fn flag(&self, name: &str) -> bool {
+        let option = format!("--{}", name);
+        self.values.contains(&option)
+    }
+
+    /// Get the value of `--name=value`, if present
+    pub fn value<T: FromStr>(&self, name: &str) -> Result<Option<T>, ArgsError> {
+        let prefix = format!("--{}=", name);
+        let Some(value) = self
+            .values
+            .iter()
+            .rev()
+            .find_map(|value| value.strip_prefix(&prefix))
+        else {
+            return Ok(None);
+        };
+
+        value.parse().map(Some).map_err(|_| ArgsError {
+            message: format!("invalid value `{}` for --{}", value, name),
+        })
+    }
+
+    /// Get the value of `--name=value`, or a default value if absent
+    pub fn value_or<T: FromStr>(&self, name: &str, default: T) -> Result<T, ArgsError> {
+        Ok(self.value(name)?.unwrap_or(default))
+    }
+
+    /// Arguments which are not options
+    pub fn positional(&self) -> Vec<&str> {
+        self.values
+            .iter()
+            .filter(|value| !value.starts_with("--"))
+            .map(|value| value.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_args() {
+        let args = Args::new([
+            "input.txt",
+            "--stream",
+            "--size=100",
+            "--size=12",
+            "--start=x",
+        ]);
+        assert!(args.flag("stream"));
+        assert!(!args.flag("size"));
+        assert_eq!(args.value::<u32>("size"), Ok(Some(12)));
+        assert_eq!(args.value_or::<u32>("other", 5), Ok(5));
+        assert!(args.value::<u32>("start").is_err());
+        assert_eq!(args.positional(), vec!["input.txt"]);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{Grid, Vector};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Offsets of the 8 cells around a position
+pub const NEIGHBORS: [Vector; 8] = [
+    Vector { x: 1, y: 0 },
+    Vector { x: 1, y: 1 },
+    Vector { x: 0, y: 1 },
+    Vector { x: -1, y: 1 },
+    Vector { x: -1, y: 0 },
+    Vector { x: -1, y: -1 },
+    Vector { x: 0, y: -1 },
+    Vector { x: 1, y: -1 },
+];
+
+/// Count the cells around a position (8 neighbors) matching a predicate
+pub fn count_neighbors<TCell>(
+    grid: &Grid<TCell>,
+    pos: &Vector,
+    predicate: impl Fn(&TCell) -> bool,
+) -> usize {
+    NEIGHBORS
+        .iter()
+        .filter_map(|dir| grid.get(&(*pos + *dir)))
+        .filter(|cell| predicate(cell))
+        .count()
+}
+
+/// How a run of the automaton ended
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    /// The last generation did not change any cell
+    Fixpoint { generation: usize },
+    /// The grid at `generation` is the same as the one `period` generations before
+    Cycle { generation: usize, period: usize },
+    /// The requested number of generations has been reached
+    Running { generation: usize },
+}
+
+/// Cellular automaton applying a local rule to all cells of a grid synchronously.
+///
+/// The rule receives the current grid, the position of a cell and its value, and returns the
+/// next value of the cell.
+pub struct Automaton<TCell, TRule> {
+    grid: Grid<TCell>,
+    rule: TRule,
+    generation: usize,
+    changed_counts: Vec<usize>,
+    /// Generation of each grid computed so far, by hash. Grids with the same hash are compared
+    /// by replaying the rule from the initial grid, which only happens once per cycle.
+    seen_grids: HashMap<u64, usize>,
+    initial_grid: Grid<TCell>,
+    /// Generation at which the current grid was already seen, if any
+    repeated_from: Option<usize>,
+}
+
+fn grid_hash<TCell: Hash>(grid: &Grid<TCell>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    grid.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<TCell, TRule> Automaton<TCell, TRule>
+where
+    TCell: Clone + Eq + Hash,
+    TRule: Fn(&Grid<TCell>, &Vector, &TCell) -> TCell,
+{
+    pub fn new(grid: Grid<TCell>, rule: TRule) -> Self {
+        let seen_grids = HashMap::from([(grid_hash(&grid), 0)]);
+        Automaton {
+            initial_grid: grid.clone(),
+            grid,
+            rule,
+            generation: 0,
+            changed_counts: vec![],
+            seen_grids,
+            repeated_from: None,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<TCell> {
+        &self.grid
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Number of changed cells for each generation computed so far
+    pub fn changed_counts(&self) -> &[usize] {
+        &self.changed_counts
+    }
+
+    /// Cells of a grid changed by the rule, with their next value
+    fn changes(&self, grid: &Grid<TCell>) -> Vec<(Vector, TCell)> {
+        let mut changes = vec![];
+        for (y, row) in grid.rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let pos = Vector {
+                    x: x as i64,
+                    y: y as i64,
+                };
+
+                let next_cell = (self.rule)(grid, &pos, cell);
+                if next_cell != *cell {
+                    changes.push((pos, next_cell));
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// Grid of a past generation, computed again from the initial grid
+    fn replay(&self, generation: usize) -> Grid<TCell> {
+        let mut grid = self.initial_grid.clone();
+        for _ in 0..generation {
+            for (pos, next_cell) in self.changes(&grid) {
+                grid[pos] = next_cell;
+            }
+        }
+
+        grid
+    }
+
+    /// Compute the next generation, returns the positions of the cells that changed
+    pub fn step(&mut self) -> Vec<Vector> {
+        // Apply all changes at once so that the rule only sees the previous generation
+        let changes = self.changes(&self.grid);
+        let changed_positions = changes.iter().map(|(pos, _)| *pos).collect::<Vec<Vector>>();
+        for (pos, next_cell) in changes {
+            self.grid[pos] = next_cell;
+        }
+
+        self.generation += 1;
+        self.changed_counts.push(changed_positions.len());
+        // Without changes, the grid is the one of the last generation
+        let previous_generation = self
+            .seen_grids
+            .insert(grid_hash(&self.grid), self.generation);
+        self.repeated_from = previous_generation.filter(|previous_generation| {
+            changed_positions.is_empty() || self.replay(*previous_generation) == self.grid
+        });
+        changed_positions
+    }
+
+    /// Run at most `generations` generations, stopping early on a fixpoint or a cycle
+    pub fn run(&mut self, generations: usize) -> Outcome {
+        for _ in 0..generations {
+            if self.step().is_empty() {
+                return Outcome::Fixpoint {
+                    generation: self.generation,
+                };
+            }
+
+            if let Some(previous_generation) = self.repeated_from {
+                return Outcome::Cycle {
+                    generation: self.generation,
+                    period: self.generation - previous_generation,
+                };
+            }
+        }
+
+        Outcome::Running {
+            generation: self.generation,
+        }
+    }
+
+    /// Run until a fixpoint or a cycle is found
+    pub fn run_until_stable(&mut self) -> Outcome {
+        self.run(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blinker() -> Grid<bool> {
+        let mut grid = Grid::with_capacity(Vector { x: 5, y: 5 }, false);
+        for x in 1..4 {
+            grid[Vector { x, y: 2 }] = true;
+        }
+
+        grid
+    }
+
+    fn game_of_life(grid: &Grid<bool>, pos: &Vector, alive: &bool) -> bool {
+        let neighbors = count_neighbors(grid, pos, |cell| *cell);
+        neighbors == 3 || (*alive && neighbors == 2)
+    }
+
+    #[test]
+    fn check_cycle() {
+        let mut automaton = Automaton::new(blinker(), game_of_life);
+        assert_eq!(automaton.step().len(), 4);
+
+        // The initial grid and the one of the manual step are part of the cycle
+        let outcome = automaton.run_until_stable();
+        assert_eq!(
+            outcome,
+            Outcome::Cycle {
+                generation: 2,
+                period: 2
+            }
+        );
+        assert_eq!(automaton.changed_counts(), &[4, 4]);
+        assert!(*automaton.grid() == blinker());
+
+        // Past generations are computed again to compare grids with the same hash
+        assert!(automaton.replay(0) == blinker());
+        assert!(automaton.replay(1) != blinker());
+        assert!(automaton.replay(3) == automaton.replay(1));
+    }
+
+    #[test]
+    fn check_fixpoint_and_limit() {
+        let mut automaton = Automaton::new(blinker(), |_: &Grid<bool>, _: &Vector, _: &bool| false);
+        assert_eq!(automaton.run(0), Outcome::Running { generation: 0 });
+        assert_eq!(automaton.run(5), Outcome::Fixpoint { generation: 2 });
+        assert!(automaton.grid().rows.iter().flatten().all(|cell| !cell));
+    }
+}
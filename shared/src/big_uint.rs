@@ -0,0 +1,182 @@
+use crate::number::{Number, OverflowError};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul};
+
+/// Unsigned integer of arbitrary size
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct BigUint {
+    // Base 2^32 digits, least significant first, without leading zeros (zero has no digits)
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+
+        self
+    }
+
+    /// Divide in place by a small divisor, returns the remainder
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0_u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let value = (remainder << 32) | *limb as u64;
+            *limb = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+
+        remainder as u32
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(len + 1);
+
+        let mut carry = 0;
+        for index in 0..len {
+            let sum = self.limbs.get(index).copied().unwrap_or(0) as u64
+                + other.limbs.get(index).copied().unwrap_or(0) as u64
+                + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        if self.is_zero() || other.is_zero() {
+            return BigUint::zero();
+        }
+
+        // Schoolbook multiplication, the product of two digits plus carries always fits in a u64
+        let mut limbs = vec![0_u32; self.limbs.len() + other.limbs.len()];
+        for (index1, &limb1) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (index2, &limb2) in other.limbs.iter().enumerate() {
+                let product = limb1 as u64 * limb2 as u64 + limbs[index1 + index2] as u64 + carry;
+                limbs[index1 + index2] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[index1 + other.limbs.len()] = carry as u32;
+        }
+
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut value: u128) -> Self {
+        let mut limbs = vec![];
+        while value > 0 {
+            limbs.push(value as u32);
+            value >>= 32;
+        }
+
+        BigUint { limbs }
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Without leading zeros, the number with more digits is the largest
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad("0");
+        }
+
+        // Extract groups of 9 decimal digits, least significant first
+        const GROUP: u32 = 1_000_000_000;
+        let mut value = self.clone();
+        let mut groups = vec![];
+        while !value.is_zero() {
+            groups.push(value.div_rem_small(GROUP));
+        }
+
+        let mut digits = groups.last().unwrap().to_string();
+        for group in groups.iter().rev().skip(1) {
+            digits.push_str(&format!("{:09}", group));
+        }
+
+        f.pad(&digits)
+    }
+}
+
+impl Number for BigUint {
+    fn from_usize(value: usize) -> Self {
+        BigUint::from(value as u128)
+    }
+
+    fn try_add(&self, other: &Self) -> Result<Self, OverflowError> {
+        Ok(self + other)
+    }
+
+    fn try_mul(&self, other: &Self) -> Result<Self, OverflowError> {
+        Ok(self * other)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_arithmetic() {
+        let value = BigUint::from(u128::MAX);
+        let one = BigUint::from(1);
+        assert_eq!(
+            (&value + &one).to_string(),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(
+            (&value * &value).to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        assert_eq!(&value * &BigUint::zero(), BigUint::zero());
+        assert_eq!(BigUint::from(1_000_000_000).to_string(), "1000000000");
+        assert_eq!(BigUint::zero().to_string(), "0");
+    }
+
+    #[test]
+    fn check_ordering() {
+        let small = BigUint::from(u64::MAX as u128);
+        let large = BigUint::from(u64::MAX as u128 + 1);
+        assert!(small < large);
+        assert!(&large * &large > &large + &large);
+        assert_eq!(&small + &BigUint::zero(), small);
+    }
+}
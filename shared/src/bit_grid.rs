@@ -0,0 +1,229 @@
+use crate::{Grid, Vector};
+
+const WORD_BITS: i64 = u64::BITS as i64;
+
+/// Boolean grid storing one bit per cell.
+///
+/// Each row is padded to a whole number of 64-bit words, cell `x` of a row being bit `x % 64` of
+/// word `x / 64`. Padding bits are always kept to zero so that word operations never need masking
+/// on read.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BitGrid {
+    size: Vector,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(size: Vector) -> BitGrid {
+        let words_per_row = (size.x.max(0) as usize).div_ceil(u64::BITS as usize);
+        let height = size.y.max(0) as usize;
+
+        BitGrid {
+            size,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn size(&self) -> &Vector {
+        &self.size
+    }
+
+    pub fn is_inside(&self, pos: &Vector) -> bool {
+        pos.x >= 0 && pos.y >= 0 && pos.x < self.size.x && pos.y < self.size.y
+    }
+
+    pub fn get(&self, pos: &Vector) -> Option<bool> {
+        if !self.is_inside(pos) {
+            return None;
+        }
+
+        let (index, bit) = self.locate(pos);
+        Some(self.words[index] & bit != 0)
+    }
+
+    /// Set a cell value, returns `false` if the position is outside the grid
+    pub fn set(&mut self, pos: &Vector, value: bool) -> bool {
+        if !self.is_inside(pos) {
+            return false;
+        }
+
+        let (index, bit) = self.locate(pos);
+        if value {
+            self.words[index] |= bit;
+        } else {
+            self.words[index] &= !bit;
+        }
+
+        true
+    }
+
+    /// Number of cells set to `true`
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Check if `mask`, once moved by `offset`, has at least one cell in common with this grid.
+    /// Parts of the mask falling outside the grid are ignored.
+    pub fn overlaps(&self, mask: &BitGrid, offset: &Vector) -> bool {
+        for y in 0..self.size.y {
+            let Some(mask_row) = mask.row(y - offset.y) else {
+                continue;
+            };
+
+            let row = self.row(y).unwrap();
+            for (word_index, word) in row.iter().enumerate() {
+                if word & shifted_word(mask_row, word_index, offset.x) != 0 {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Set all cells of `mask`, once moved by `offset`, in this grid.
+    /// Parts of the mask falling outside the grid are dropped.
+    pub fn or_shifted(&mut self, mask: &BitGrid, offset: &Vector) {
+        for y in 0..self.size.y {
+            let Some(mask_row) = mask.row(y - offset.y) else {
+                continue;
+            };
+
+            let row_start = y as usize * self.words_per_row;
+            for word_index in 0..self.words_per_row {
+                let word = shifted_word(mask_row, word_index, offset.x);
+                self.words[row_start + word_index] |= word & self.row_mask(word_index);
+            }
+        }
+    }
+
+    /// Copy of this grid with all cells moved by `offset`, cells moved outside being dropped
+    pub fn shift(&self, offset: &Vector) -> BitGrid {
+        let mut shifted = BitGrid::new(self.size);
+        shifted.or_shifted(self, offset);
+        shifted
+    }
+
+    fn locate(&self, pos: &Vector) -> (usize, u64) {
+        let index = pos.y as usize * self.words_per_row + (pos.x / WORD_BITS) as usize;
+        (index, 1 << (pos.x % WORD_BITS))
+    }
+
+    fn row(&self, y: i64) -> Option<&[u64]> {
+        if y < 0 || y >= self.size.y {
+            return None;
+        }
+
+        let start = y as usize * self.words_per_row;
+        Some(&self.words[start..start + self.words_per_row])
+    }
+
+    /// Mask of the valid (non padding) bits for a word of a row
+    fn row_mask(&self, word_index: usize) -> u64 {
+        let remaining_bits = self.size.x - word_index as i64 * WORD_BITS;
+        if remaining_bits >= WORD_BITS {
+            u64::MAX
+        } else {
+            (1 << remaining_bits) - 1
+        }
+    }
+}
+
+/// Get the word `word_index` of a row whose bits have been moved by `shift` cells
+/// (towards higher x when positive).
+fn shifted_word(row: &[u64], word_index: usize, shift: i64) -> u64 {
+    let word_at = |index: i64| -> u64 {
+        if index < 0 {
+            return 0;
+        }
+
+        row.get(index as usize).copied().unwrap_or(0)
+    };
+
+    // First source bit landing in the requested word
+    let start = word_index as i64 * WORD_BITS - shift;
+    let source_index = start.div_euclid(WORD_BITS);
+    let bit_offset = start.rem_euclid(WORD_BITS);
+    if bit_offset == 0 {
+        return word_at(source_index);
+    }
+
+    (word_at(source_index) >> bit_offset) | (word_at(source_index + 1) << (WORD_BITS - bit_offset))
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(grid: &Grid<bool>) -> Self {
+        let mut bit_grid = BitGrid::new(*grid.size());
+        for (y, row) in grid.rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let pos = Vector {
+                    x: x as i64,
+                    y: y as i64,
+                };
+
+                bit_grid.set(&pos, *cell);
+            }
+        }
+
+        bit_grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_str(raw_data: &str) -> BitGrid {
+        let rows = raw_data
+            .lines()
+            .map(|line| line.chars().map(|c| c == '#').collect())
+            .collect();
+
+        BitGrid::from(&Grid::new(rows))
+    }
+
+    #[test]
+    fn check_get_set() {
+        let mut grid = BitGrid::new(Vector { x: 130, y: 2 });
+        assert!(grid.set(&Vector { x: 129, y: 1 }, true));
+        assert!(!grid.set(&Vector { x: 130, y: 1 }, true));
+        assert_eq!(grid.get(&Vector { x: 129, y: 1 }), Some(true));
+        assert_eq!(grid.get(&Vector { x: 128, y: 1 }), Some(false));
+        assert_eq!(grid.get(&Vector { x: -1, y: 0 }), None);
+        assert_eq!(grid.count_ones(), 1);
+    }
+
+    #[test]
+    fn check_overlaps_and_or_shifted() {
+        let mut grid = from_str("#....\n.....\n.....");
+        let mask = from_str("##\n#.");
+
+        assert!(grid.overlaps(&mask, &Vector { x: 0, y: 0 }));
+        assert!(grid.overlaps(&mask, &Vector { x: -1, y: 0 }));
+        assert!(!grid.overlaps(&mask, &Vector { x: 1, y: 0 }));
+
+        grid.or_shifted(&mask, &Vector { x: 4, y: 1 });
+        assert_eq!(grid, from_str("#....\n....#\n....#"));
+        assert_eq!(grid.count_ones(), 3);
+    }
+
+    #[test]
+    fn check_shift_across_words() {
+        let mut grid = BitGrid::new(Vector { x: 200, y: 1 });
+        grid.set(&Vector { x: 60, y: 0 }, true);
+        grid.set(&Vector { x: 199, y: 0 }, true);
+
+        let shifted = grid.shift(&Vector { x: 10, y: 0 });
+        assert_eq!(shifted.get(&Vector { x: 70, y: 0 }), Some(true));
+        assert_eq!(shifted.count_ones(), 1);
+
+        let shifted = shifted.shift(&Vector { x: -70, y: 0 });
+        assert_eq!(shifted.get(&Vector { x: 0, y: 0 }), Some(true));
+        assert_eq!(shifted.count_ones(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::Rng;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Generated input for which a solver and its reference disagree
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Disagreement {
+    pub seed: u64,
+    pub size: usize,
+    pub input: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "solver and reference disagree (seed {}, size {})",
+            self.seed, self.size
+        )?;
+        writeln!(f, "  expected: {}", self.expected)?;
+        writeln!(f, "  actual:   {}", self.actual)?;
+        write!(f, "  input:\n{}", self.input)
+    }
+}
+
+/// Run a solver and a reference solver on generated inputs, for each size (in increasing order)
+/// and seeds from 0 to `seed_count`. Returns the smallest input they disagree on: the one with the
+/// smallest size, then the shortest one.
+pub fn find_disagreement<T: PartialEq + fmt::Debug>(
+    generate: impl Fn(&mut Rng, usize) -> String,
+    sizes: RangeInclusive<usize>,
+    seed_count: u64,
+    solve: impl Fn(&str) -> T,
+    reference: impl Fn(&str) -> T,
+) -> Option<Disagreement> {
+    for size in sizes {
+        let smallest = (0..seed_count)
+            .filter_map(|seed| {
+                let input = generate(&mut Rng::new(seed), size);
+                let expected = reference(&input);
+                let actual = solve(&input);
+                (actual != expected).then(|| Disagreement {
+                    seed,
+                    size,
+                    input,
+                    expected: format!("{:?}", expected),
+                    actual: format!("{:?}", actual),
+                })
+            })
+            .min_by_key(|disagreement| disagreement.input.len());
+
+        if smallest.is_some() {
+            return smallest;
+        }
+    }
+
+    None
+}
+
+/// Panic with the smallest disagreement between a solver and a reference solver, if any
+pub fn assert_agree<T: PartialEq + fmt::Debug>(
+    generate: impl Fn(&mut Rng, usize) -> String,
+    sizes: RangeInclusive<usize>,
+    seed_count: u64,
+    solve: impl Fn(&str) -> T,
+    reference: impl Fn(&str) -> T,
+) {
+    if let Some(disagreement) = find_disagreement(generate, sizes, seed_count, solve, reference) {
+        panic!("{}", disagreement);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        (0..size).map(|_| rng.between(0, 9).to_string()).collect()
+    }
+
+    fn digit_sum(input: &str) -> u32 {
+        input.chars().map(|c| c.to_digit(10).unwrap()).sum()
+    }
+
+    #[test]
+    fn check_smallest_disagreement() {
+        assert_agree(generate, 1..=5, 20, digit_sum, digit_sum);
+
+        // Wrong as soon as the input contains a 9
+        let solve = |input: &str| digit_sum(&input.replace('9', "8"));
+        let disagreement = find_disagreement(generate, 1..=5, 20, solve, digit_sum).unwrap();
+        assert!(disagreement.input.contains('9'));
+        assert!(
+            find_disagreement(generate, 1..=disagreement.size - 1, 20, solve, digit_sum).is_none()
+        );
+
+        // The smallest input has a single 9, counted one less
+        assert_eq!(disagreement.input.matches('9').count(), 1);
+        let [expected, actual] = [&disagreement.expected, &disagreement.actual]
+            .map(|answer| answer.parse::<u32>().unwrap());
+        assert_eq!(expected, actual + 1);
+    }
+}
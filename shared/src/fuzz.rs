@@ -0,0 +1,192 @@
+use crate::Rng;
+use crate::args::Args;
+use std::error::Error;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Fragments likely to hit edge cases of the puzzle parsers
+const TOKENS: [&str; 24] = [
+    "0",
+    "1",
+    "-1",
+    "9",
+    "99999999999999999999",
+    "18446744073709551615",
+    "-9223372036854775808",
+    "\n",
+    "\n\n",
+    "\r\n",
+    " ",
+    ",",
+    "-",
+    ":",
+    "x",
+    "#",
+    ".",
+    "@",
+    "(",
+    "[",
+    "{",
+    "*",
+    "S",
+    "out",
+];
+
+/// Input on which a target panicked
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Crash {
+    pub seed: u64,
+    pub input: String,
+    pub message: String,
+}
+
+impl fmt::Display for Crash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "panic on fuzzed input (seed {}): {}",
+            self.seed, self.message
+        )?;
+        write!(f, "  input: {:?}", self.input)
+    }
+}
+
+impl Error for Crash {}
+
+/// Apply a few random mutations to some bytes: flips, insertions, deletions, duplicated chunks,
+/// tokens and truncation
+pub fn mutate(rng: &mut Rng, input: &[u8]) -> Vec<u8> {
+    let mut bytes = input.to_vec();
+    for _ in 0..rng.between(1, 4) {
+        let pos = rng.between(0, bytes.len());
+        match rng.between(0, 5) {
+            0 if pos < bytes.len() => bytes[pos] ^= 1 << rng.between(0, 7),
+            1 => bytes.insert(pos, rng.between(0, 255) as u8),
+            2 => {
+                let end = rng.between(pos, bytes.len().min(pos + 8));
+                bytes.drain(pos..end);
+            }
+            3 => {
+                let end = rng.between(pos, bytes.len().min(pos + 16));
+                let chunk = bytes[pos..end].to_vec();
+                bytes.splice(pos..pos, chunk);
+            }
+            4 => {
+                let token = rng.choose(&TOKENS);
+                bytes.splice(pos..pos, token.bytes());
+            }
+            _ => bytes.truncate(pos),
+        }
+    }
+
+    bytes
+}
+
+/// Fuzzed input for a seed: most of the time a mutated corpus entry, sometimes random bytes. Bytes
+/// which are not valid UTF-8 are replaced, as inputs are read as text.
+pub fn fuzzed_input(corpus: &[&str], seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let bytes = if corpus.is_empty() || rng.chance(0.05) {
+        let len = rng.between(0, 64);
+        (0..len).map(|_| rng.between(0, 255) as u8).collect()
+    } else {
+        let entry = rng.choose(corpus);
+        mutate(&mut rng, entry.as_bytes())
+    };
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Run a target on fuzzed inputs for seeds `first_seed..first_seed + count`, returns the first
+/// input it panics on. The target may fail, but not panic.
+pub fn find_crash<T>(
+    corpus: &[&str],
+    first_seed: u64,
+    count: u64,
+    target: impl Fn(&str) -> T,
+) -> Option<Crash> {
+    for seed in first_seed..first_seed + count {
+        let input = fuzzed_input(corpus, seed);
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| target(&input))) {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            return Some(Crash {
+                seed,
+                input,
+                message,
+            });
+        }
+    }
+
+    None
+}
+
+/// Panic with the first fuzzed input a target panics on, if any
+pub fn assert_no_crash<T>(corpus: &[&str], count: u64, target: impl Fn(&str) -> T) {
+    if let Some(crash) = find_crash(corpus, 0, count, target) {
+        panic!("{}", crash);
+    }
+}
+
+/// Fuzz a target if `--fuzz=N` is given on the command line (N inputs, with optional `--seed=N`
+/// for the first one), returns `false` if the input should be solved as usual. The corpus is the
+/// puzzle input and an input from `generate`, only generated when fuzzing.
+pub fn run_from_args<T>(
+    args: &Args,
+    raw_data: &str,
+    generate: impl Fn(&mut Rng, usize) -> String,
+    target: impl Fn(&str) -> T,
+) -> Result<bool, Box<dyn Error>> {
+    let Some(count) = args.value::<u64>("fuzz")? else {
+        return Ok(false);
+    };
+
+    let first_seed = args.value_or("seed", 0)?;
+    let generated = generate(&mut Rng::new(0), 10);
+    match find_crash(&[raw_data, &generated], first_seed, count, target) {
+        Some(crash) => Err(crash.into()),
+        None => {
+            println!("No panic on {} fuzzed inputs", count);
+            Ok(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_mutate() {
+        let corpus = ["12-34,56-78"];
+        assert_eq!(fuzzed_input(&corpus, 3), fuzzed_input(&corpus, 3));
+        assert!((0..100).any(|seed| fuzzed_input(&corpus, seed) != corpus[0]));
+    }
+
+    #[test]
+    fn check_find_crash() {
+        let corpus = ["1,2,3"];
+        let parse = |input: &str| -> Result<Vec<u8>, std::num::ParseIntError> {
+            input.split(',').map(|value| value.parse()).collect()
+        };
+        assert_no_crash(&corpus, 200, parse);
+
+        // The report of the expected panic is captured with the output of this test, the panic
+        // hook being shared with the tests running in parallel
+        let crash = find_crash(&corpus, 0, 1000, |input| parse(input).unwrap()).unwrap();
+        assert!(parse(&crash.input).is_err());
+        assert!(crash.message.contains("ParseIntError"));
+    }
+
+    #[test]
+    fn check_run_from_args() {
+        let generate = |_: &mut Rng, _: usize| -> String { panic!("generated without fuzzing") };
+        assert!(!run_from_args(&Args::new(["input.txt"]), "1,2", generate, |_| ()).unwrap());
+
+        let generate = |rng: &mut Rng, size: usize| rng.between(0, size).to_string();
+        assert!(run_from_args(&Args::new(["--fuzz=50"]), "1,2", generate, |_| ()).unwrap());
+    }
+}
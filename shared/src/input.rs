@@ -0,0 +1,154 @@
+use crate::parse::{ParseError, Parser};
+use std::fmt;
+use std::io::{self, BufRead};
+
+const BOM: char = '\u{feff}';
+
+/// Whether whitespace at the end of lines is part of the puzzle input
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TrailingWhitespace {
+    /// Keep trailing whitespace (e.g. for column aligned inputs)
+    Significant,
+    /// Remove trailing whitespace of each line, and blank lines at the end of the input
+    Trim,
+}
+
+/// Normalize a raw input so that it parses the same whatever the machine it was saved on:
+/// the BOM is removed, and `\r\n` or `\r` line endings are converted to `\n`.
+pub fn normalize(raw_data: &str, trailing_whitespace: TrailingWhitespace) -> String {
+    let raw_data = raw_data.strip_prefix(BOM).unwrap_or(raw_data);
+    let raw_data = raw_data.replace("\r\n", "\n").replace('\r', "\n");
+
+    match trailing_whitespace {
+        TrailingWhitespace::Significant => raw_data,
+        TrailingWhitespace::Trim => {
+            let mut normalized = String::with_capacity(raw_data.len());
+            for line in raw_data.trim_end().lines() {
+                normalized.push_str(line.trim_end());
+                normalized.push('\n');
+            }
+
+            normalized
+        }
+    }
+}
+
+/// Normalize a single line read from an input, removing its line ending
+pub fn normalize_line(line: &str, trailing_whitespace: TrailingWhitespace) -> &str {
+    let line = line.strip_prefix(BOM).unwrap_or(line);
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+
+    match trailing_whitespace {
+        TrailingWhitespace::Significant => line,
+        TrailingWhitespace::Trim => line.trim_end(),
+    }
+}
+
+/// Error raised while reading and parsing an input
+#[derive(Debug)]
+pub enum InputError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(error) => write!(f, "{}", error),
+            InputError::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<io::Error> for InputError {
+    fn from(error: io::Error) -> Self {
+        InputError::Io(error)
+    }
+}
+
+impl From<ParseError> for InputError {
+    fn from(error: ParseError) -> Self {
+        InputError::Parse(error)
+    }
+}
+
+/// Read an input line by line, reusing the same buffer so that memory stays bounded whatever the
+/// input size. Lines are normalized like [`normalize`] does (except for `\r` only line endings).
+pub struct LineReader<R> {
+    reader: R,
+    buffer: String,
+    line_number: usize,
+    trailing_whitespace: TrailingWhitespace,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(reader: R, trailing_whitespace: TrailingWhitespace) -> Self {
+        LineReader {
+            reader,
+            buffer: String::new(),
+            line_number: 0,
+            trailing_whitespace,
+        }
+    }
+
+    /// Read the next line, returns a parser over it (reporting errors at the right line number),
+    /// or `None` at the end of the input
+    pub fn next_line(&mut self) -> io::Result<Option<Parser<'_>>> {
+        self.buffer.clear();
+        if self.reader.read_line(&mut self.buffer)? == 0 {
+            return Ok(None);
+        }
+
+        self.line_number += 1;
+        let line = normalize_line(&self.buffer, self.trailing_whitespace);
+        Ok(Some(Parser::at_line(line, self.line_number)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW_INPUT: &str = "\u{feff}12 4 \r\n 3  \r\r\n\r\n";
+
+    #[test]
+    fn check_normalize() {
+        assert_eq!(
+            normalize(RAW_INPUT, TrailingWhitespace::Significant),
+            "12 4 \n 3  \n\n\n"
+        );
+        assert_eq!(normalize(RAW_INPUT, TrailingWhitespace::Trim), "12 4\n 3\n");
+        assert_eq!(normalize(" \n\n", TrailingWhitespace::Trim), "");
+    }
+
+    #[test]
+    fn check_normalize_line() {
+        assert_eq!(
+            normalize_line("\u{feff}L68 \r\n", TrailingWhitespace::Trim),
+            "L68"
+        );
+        assert_eq!(
+            normalize_line(" + \n", TrailingWhitespace::Significant),
+            " + "
+        );
+    }
+
+    #[test]
+    fn check_line_reader() {
+        let mut reader = LineReader::new(RAW_INPUT.as_bytes(), TrailingWhitespace::Trim);
+        let mut line = reader.next_line().unwrap().unwrap();
+        assert_eq!(line.unsigned::<u32>(), Ok(12));
+
+        let error = line.literal(",").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 3));
+
+        let mut lines = vec![];
+        while let Some(line) = reader.next_line().unwrap() {
+            lines.push(line.rest().to_string());
+        }
+        assert_eq!(lines, vec![" 3", ""]);
+    }
+}
@@ -0,0 +1,272 @@
+/// Inclusive range of integers
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Range {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Range {
+    pub fn new(min: usize, max: usize) -> Range {
+        Range { min, max }
+    }
+
+    pub fn is_inside(&self, value: usize) -> bool {
+        value >= self.min && value <= self.max
+    }
+
+    /// Extend this range with another one if they overlap, returns `false` otherwise
+    pub fn merge(&mut self, other: &Range) -> bool {
+        if self.min <= other.max && self.max >= other.min {
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Number of integers in the range, `None` if it does not fit in a `usize` (for `0..=usize::MAX`)
+    pub fn size(&self) -> Option<usize> {
+        match self.max.checked_sub(self.min) {
+            Some(difference) => difference.checked_add(1),
+            None => Some(0),
+        }
+    }
+
+    /// Common part of both ranges, if any
+    pub fn intersection(&self, other: &Range) -> Option<Range> {
+        let min = self.min.max(other.min);
+        let max = self.max.min(other.max);
+        (min <= max).then_some(Range { min, max })
+    }
+}
+
+/// Set of integers stored as sorted, disjoint and non adjacent ranges
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct IntervalSet {
+    ranges: Vec<Range>,
+}
+
+impl IntervalSet {
+    /// Build a set from any ranges, overlapping or adjacent ranges being merged together.
+    /// Ranges with `min > max` are empty and ignored.
+    pub fn new(ranges: impl IntoIterator<Item = Range>) -> IntervalSet {
+        let mut sorted_ranges: Vec<Range> = ranges
+            .into_iter()
+            .filter(|range| range.min <= range.max)
+            .collect();
+        sorted_ranges.sort_by_key(|range| range.min);
+
+        // Single pass over sorted ranges: each range either extends the last one or starts a new one
+        let mut merged_ranges: Vec<Range> = Vec::with_capacity(sorted_ranges.len());
+        for range in sorted_ranges {
+            match merged_ranges.last_mut() {
+                Some(last) if range.min <= last.max.saturating_add(1) => {
+                    last.max = last.max.max(range.max);
+                }
+                _ => merged_ranges.push(range),
+            }
+        }
+
+        IntervalSet {
+            ranges: merged_ranges,
+        }
+    }
+
+    /// Number of merged ranges
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        // First range ending at or after the value
+        let index = self.ranges.partition_point(|range| range.max < value);
+        self.ranges
+            .get(index)
+            .is_some_and(|range| range.is_inside(value))
+    }
+
+    /// Number of integers in the set, `None` if it does not fit in a `usize`
+    pub fn size(&self) -> Option<usize> {
+        self.ranges
+            .iter()
+            .try_fold(0_usize, |size, range| size.checked_add(range.size()?))
+    }
+
+    /// Iterate over the merged ranges, in increasing order
+    pub fn iter(&self) -> std::slice::Iter<'_, Range> {
+        self.ranges.iter()
+    }
+
+    /// Integers in either set
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::new(self.ranges.iter().chain(other.ranges.iter()).copied())
+    }
+
+    /// Integers in both sets
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(range1), Some(range2)) = (self.ranges.get(i), other.ranges.get(j)) {
+            if let Some(common) = range1.intersection(range2) {
+                ranges.push(common);
+            }
+
+            // Drop the range ending first, it cannot intersect anything else
+            if range1.max < range2.max {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        // Both inputs being normalized, intersections are already sorted and disjoint
+        IntervalSet { ranges }
+    }
+
+    /// Integers in this set but not in `other`
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut ranges = vec![];
+        let mut j = 0;
+        for range in &self.ranges {
+            // Skip removed ranges ending before this range
+            while other
+                .ranges
+                .get(j)
+                .is_some_and(|removed| removed.max < range.min)
+            {
+                j += 1;
+            }
+
+            // Keep the gaps between removed ranges overlapping this range
+            let mut start = Some(range.min);
+            for removed in other.ranges[j..]
+                .iter()
+                .take_while(|removed| removed.min <= range.max)
+            {
+                let Some(gap_start) = start else {
+                    break;
+                };
+
+                if removed.min > gap_start {
+                    ranges.push(Range::new(gap_start, removed.min - 1));
+                }
+
+                start = (removed.max < range.max).then(|| removed.max + 1);
+            }
+
+            if let Some(gap_start) = start {
+                ranges.push(Range::new(gap_start, range.max));
+            }
+        }
+
+        IntervalSet { ranges }
+    }
+
+    /// Integers in exactly one of the sets
+    pub fn symmetric_difference(&self, other: &IntervalSet) -> IntervalSet {
+        self.difference(other).union(&other.difference(self))
+    }
+
+    /// Integers of `domain` which are not in this set
+    pub fn complement(&self, domain: &Range) -> IntervalSet {
+        IntervalSet::new([*domain]).difference(self)
+    }
+}
+
+impl FromIterator<Range> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Range>>(iter: T) -> Self {
+        IntervalSet::new(iter)
+    }
+}
+
+impl<'a> IntoIterator for &'a IntervalSet {
+    type Item = &'a Range;
+    type IntoIter = std::slice::Iter<'a, Range>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_normalization() {
+        let set = IntervalSet::new([
+            Range::new(10, 14),
+            Range::new(3, 5),
+            Range::new(16, 20),
+            Range::new(12, 18),
+            Range::new(6, 6),
+            Range::new(30, 25),
+        ]);
+
+        let ranges: Vec<Range> = set.iter().copied().collect();
+        assert_eq!(ranges, vec![Range::new(3, 6), Range::new(10, 20)]);
+        assert_eq!(set.size(), Some(15));
+
+        // Every `usize` is one more than the largest one
+        assert_eq!(Range::new(1, usize::MAX).size(), Some(usize::MAX));
+        assert_eq!(Range::new(0, usize::MAX).size(), None);
+        assert_eq!(Range::new(30, 25).size(), Some(0));
+        let set = IntervalSet::new([Range::new(0, 5), Range::new(7, usize::MAX)]);
+        assert_eq!(set.size(), Some(usize::MAX));
+        assert_eq!(
+            set.union(&IntervalSet::new([Range::new(6, 6)])).size(),
+            None
+        );
+    }
+
+    #[test]
+    fn check_contains() {
+        let set: IntervalSet = [Range::new(3, 5), Range::new(10, 14)].into_iter().collect();
+        let contained: Vec<usize> = (0..16).filter(|value| set.contains(*value)).collect();
+        assert_eq!(contained, vec![3, 4, 5, 10, 11, 12, 13, 14]);
+        assert!(!IntervalSet::default().contains(0));
+    }
+
+    fn set(ranges: &[(usize, usize)]) -> IntervalSet {
+        ranges
+            .iter()
+            .map(|(min, max)| Range::new(*min, *max))
+            .collect()
+    }
+
+    #[test]
+    fn check_set_algebra() {
+        let set1 = set(&[(0, 5), (10, 20), (30, 30)]);
+        let set2 = set(&[(3, 12), (15, 16), (20, 40)]);
+
+        assert_eq!(set1.union(&set2), set(&[(0, 40)]));
+        assert_eq!(
+            set1.intersection(&set2),
+            set(&[(3, 5), (10, 12), (15, 16), (20, 20), (30, 30)])
+        );
+        assert_eq!(set1.difference(&set2), set(&[(0, 2), (13, 14), (17, 19)]));
+        assert_eq!(
+            set1.symmetric_difference(&set2),
+            set(&[(0, 2), (6, 9), (13, 14), (17, 19), (21, 29), (31, 40)])
+        );
+    }
+
+    #[test]
+    fn check_complement() {
+        let set1 = set(&[(0, 5), (10, 20), (usize::MAX, usize::MAX)]);
+        assert_eq!(set1.complement(&Range::new(3, 12)), set(&[(6, 9)]));
+        assert_eq!(
+            set1.complement(&Range::new(0, usize::MAX)),
+            set(&[(6, 9), (21, usize::MAX - 1)])
+        );
+        assert_eq!(
+            IntervalSet::default().complement(&Range::new(1, 2)),
+            set(&[(1, 2)])
+        );
+    }
+}
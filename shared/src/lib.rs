@@ -1,5 +1,27 @@
+pub mod args;
+pub mod automaton;
+mod big_uint;
+mod bit_grid;
+pub mod differential;
+pub mod fuzz;
 mod grid;
+pub mod input;
+mod interval_set;
+pub mod number;
+pub mod parse;
+pub mod property;
+pub mod random;
+mod summed_area_table;
+pub mod trace;
+mod union_find;
 mod vector;
 
+pub use automaton::Automaton;
+pub use big_uint::BigUint;
+pub use bit_grid::BitGrid;
 pub use grid::Grid;
+pub use interval_set::{IntervalSet, Range};
+pub use random::Rng;
+pub use summed_area_table::SummedAreaTable;
+pub use union_find::UnionFind;
 pub use vector::Vector;
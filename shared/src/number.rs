@@ -0,0 +1,148 @@
+use crate::BigUint;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error raised when a result does not fit in the integer type used by a solver
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OverflowError {
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arithmetic overflow with {} integers (run with a wider --precision)",
+            self.type_name
+        )
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// Unsigned integer type a solver can compute with, all operations being checked
+pub trait Number: Clone + Ord + fmt::Display + fmt::Debug {
+    fn from_usize(value: usize) -> Self;
+
+    fn try_add(&self, other: &Self) -> Result<Self, OverflowError>;
+
+    fn try_mul(&self, other: &Self) -> Result<Self, OverflowError>;
+
+    fn zero() -> Self {
+        Self::from_usize(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+macro_rules! impl_number {
+    ($($type:ty),*) => {
+        $(
+            impl Number for $type {
+                fn from_usize(value: usize) -> Self {
+                    value as $type
+                }
+
+                fn try_add(&self, other: &Self) -> Result<Self, OverflowError> {
+                    self.checked_add(*other).ok_or(OverflowError {
+                        type_name: stringify!($type),
+                    })
+                }
+
+                fn try_mul(&self, other: &Self) -> Result<Self, OverflowError> {
+                    self.checked_mul(*other).ok_or(OverflowError {
+                        type_name: stringify!($type),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(usize, u64, u128);
+
+/// Integer type used to compute results, selected per run with `--precision`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Precision {
+    /// Compute with `usize`, failing on overflow
+    Checked,
+    /// Compute with `u128`
+    Wide,
+    /// Compute with arbitrary precision integers, which never overflow
+    Big,
+    /// Compute with `usize`, and compute again with wider types on overflow
+    Auto,
+}
+
+impl FromStr for Precision {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "checked" => Ok(Precision::Checked),
+            "wide" => Ok(Precision::Wide),
+            "big" => Ok(Precision::Big),
+            "auto" => Ok(Precision::Auto),
+            _ => Err(format!("unknown precision `{}`", value)),
+        }
+    }
+}
+
+/// Computation generic over the integer type of its result
+pub trait Solve {
+    fn solve<T: Number>(&self) -> Result<T, OverflowError>;
+}
+
+/// Run a computation with the integer type matching the requested precision
+pub fn solve_with<S: Solve>(solver: &S, precision: Precision) -> Result<String, OverflowError> {
+    match precision {
+        Precision::Checked => solver.solve::<usize>().map(|value| value.to_string()),
+        Precision::Wide => solver.solve::<u128>().map(|value| value.to_string()),
+        Precision::Big => solver.solve::<BigUint>().map(|value| value.to_string()),
+        Precision::Auto => solve_with(solver, Precision::Checked)
+            .or_else(|_| solve_with(solver, Precision::Wide))
+            .or_else(|_| solve_with(solver, Precision::Big)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Power(u32);
+
+    impl Solve for Power {
+        fn solve<T: Number>(&self) -> Result<T, OverflowError> {
+            let mut value = T::from_usize(1);
+            for _ in 0..self.0 {
+                value = value.try_mul(&T::from_usize(2))?;
+            }
+
+            Ok(value)
+        }
+    }
+
+    #[test]
+    fn check_overflow() {
+        assert_eq!(
+            u64::MAX.try_add(&1),
+            Err(OverflowError { type_name: "u64" })
+        );
+        assert_eq!(
+            solve_with(&Power(10), Precision::Checked),
+            Ok("1024".to_string())
+        );
+        assert!(solve_with(&Power(64), Precision::Checked).is_err());
+        assert_eq!(
+            solve_with(&Power(64), Precision::Auto),
+            Ok("18446744073709551616".to_string())
+        );
+        assert!(solve_with(&Power(128), Precision::Wide).is_err());
+        assert_eq!(
+            solve_with(&Power(128), Precision::Auto),
+            Ok("340282366920938463463374607431768211456".to_string())
+        );
+    }
+}
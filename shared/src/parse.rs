@@ -0,0 +1,450 @@
+use crate::{Grid, Range};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Error raised when an input does not match the expected format
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    /// Line of the error, starting at 1
+    pub line: usize,
+    /// Column of the error (in characters), starting at 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Cursor over an input, consuming it from left to right.
+///
+/// Sub-parsers (lines, sections, bracket groups) keep a reference to the whole input so that
+/// errors are always reported with their position in the original input.
+#[derive(Clone, Copy, Debug)]
+pub struct Parser<'a> {
+    source: &'a str,
+    first_line: usize,
+    pos: usize,
+    end: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Parser<'a> {
+        Self::at_line(input, 1)
+    }
+
+    /// Parser over an input starting at a given line number (for inputs read line by line)
+    pub fn at_line(input: &'a str, line: usize) -> Parser<'a> {
+        Parser {
+            source: input,
+            first_line: line,
+            pos: 0,
+            end: input.len(),
+        }
+    }
+
+    /// Remaining input
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.pos..self.end]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.end
+    }
+
+    /// Error located at the current position
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, offset: usize, message: impl Into<String>) -> ParseError {
+        let before = &self.source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        ParseError {
+            line: self.first_line + before.matches('\n').count(),
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn next_char(&mut self) -> Result<char, ParseError> {
+        let c = self
+            .peek()
+            .ok_or_else(|| self.error("unexpected end of input"))?;
+        self.pos += c.len_utf8();
+        Ok(c)
+    }
+
+    /// Consume characters while they match a predicate
+    pub fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// Skip spaces and tabs
+    pub fn skip_spaces(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t');
+    }
+
+    /// Skip all whitespace, including line breaks
+    pub fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Consume `expected` if the input starts with it
+    pub fn try_literal(&mut self, expected: &str) -> bool {
+        if self.rest().starts_with(expected) {
+            self.pos += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn literal(&mut self, expected: &str) -> Result<(), ParseError> {
+        if self.try_literal(expected) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", expected)))
+        }
+    }
+
+    /// Parse an integer made of decimal digits only
+    pub fn unsigned<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(self.error("expected an unsigned integer"));
+        }
+
+        digits
+            .parse()
+            .map_err(|_| self.error_at(start, format!("integer `{}` out of range", digits)))
+    }
+
+    /// Parse an integer with an optional `-` or `+` sign
+    pub fn signed<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        if !self.try_literal("-") {
+            self.try_literal("+");
+        }
+
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(self.error("expected an integer"));
+        }
+
+        let number = &self.source[start..self.pos];
+        number
+            .parse()
+            .map_err(|_| self.error_at(start, format!("integer `{}` out of range", number)))
+    }
+
+    /// Parse an integer (with an optional sign) and check that it is within `bounds`
+    pub fn bounded<T: FromStr + PartialOrd + fmt::Display>(
+        &mut self,
+        bounds: RangeInclusive<T>,
+    ) -> Result<T, ParseError> {
+        let start = self.pos;
+        let value = self.signed()?;
+        if !bounds.contains(&value) {
+            return Err(self.error_at(
+                start,
+                format!(
+                    "integer `{}` out of range {}..={}",
+                    value,
+                    bounds.start(),
+                    bounds.end()
+                ),
+            ));
+        }
+
+        Ok(value)
+    }
+
+    /// Parse the rest of the input one character at a time, `cell` giving the value of each
+    /// character (or `None` if the character is invalid)
+    pub fn cells<T>(&mut self, cell: impl Fn(char) -> Option<T>) -> Result<Vec<T>, ParseError> {
+        let mut cells = vec![];
+        while let Some(c) = self.peek() {
+            cells.push(cell(c).ok_or_else(|| self.error(format!("unexpected `{}`", c)))?);
+            self.next_char()?;
+        }
+
+        Ok(cells)
+    }
+
+    /// Parse an unsigned integer written in a base from 2 to 36 (digits after 9 being letters, in
+    /// lowercase or uppercase). Other bases are reported as an error.
+    pub fn unsigned_radix(&mut self, radix: u32) -> Result<usize, ParseError> {
+        if !(2..=36).contains(&radix) {
+            return Err(self.error(format!("unsupported base {}, expected 2 to 36", radix)));
+        }
+
+        let start = self.pos;
+        let digits = self.take_while(|c| c.is_digit(radix));
+        if digits.is_empty() {
+            return Err(self.error(format!("expected an unsigned integer in base {}", radix)));
+        }
+
+        usize::from_str_radix(digits, radix)
+            .map_err(|_| self.error_at(start, format!("integer `{}` out of range", digits)))
+    }
+
+    /// Parse a `min-max` range
+    pub fn range(&mut self) -> Result<Range, ParseError> {
+        let min = self.unsigned()?;
+        self.literal("-")?;
+        let max = self.unsigned()?;
+        Ok(Range { min, max })
+    }
+
+    /// Parse a `min-max` range of integers written in a base from 2 to 36
+    pub fn range_radix(&mut self, radix: u32) -> Result<Range, ParseError> {
+        let min = self.unsigned_radix(radix)?;
+        self.literal("-")?;
+        let max = self.unsigned_radix(radix)?;
+        Ok(Range { min, max })
+    }
+
+    /// Parse one or more items separated by `separator`
+    pub fn separated<T>(
+        &mut self,
+        separator: &str,
+        mut item: impl FnMut(&mut Parser<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        while self.try_literal(separator) {
+            items.push(item(self)?);
+        }
+
+        Ok(items)
+    }
+
+    /// Consume a group enclosed between `open` and `close` (nested groups of the same kind are
+    /// allowed), and return a parser over its content
+    pub fn delimited(&mut self, open: char, close: char) -> Result<Parser<'a>, ParseError> {
+        if self.peek() != Some(open) {
+            return Err(self.error(format!("expected `{}`", open)));
+        }
+
+        let start = self.pos;
+        let mut depth = 0;
+        for (index, c) in self.rest().char_indices() {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    let content = Parser {
+                        pos: start + open.len_utf8(),
+                        end: start + index,
+                        ..*self
+                    };
+
+                    self.pos = start + index + close.len_utf8();
+                    return Ok(content);
+                }
+            }
+        }
+
+        Err(self.error_at(start, format!("unclosed `{}`", open)))
+    }
+
+    /// Parsers over each line of the remaining input (without line breaks)
+    pub fn lines(&self) -> impl Iterator<Item = Parser<'a>> + use<'a> {
+        let parser = *self;
+        let mut start = self.pos;
+        std::iter::from_fn(move || {
+            if start >= parser.end {
+                return None;
+            }
+
+            let line_end = parser.source[start..parser.end]
+                .find('\n')
+                .map_or(parser.end, |index| start + index);
+            let line = &parser.source[start..line_end];
+            let line = Parser {
+                pos: start,
+                end: start + line.strip_suffix('\r').unwrap_or(line).len(),
+                ..parser
+            };
+
+            start = line_end + 1;
+            Some(line)
+        })
+    }
+
+    /// Parsers over each group of lines of the remaining input, groups being separated by blank
+    /// lines
+    pub fn sections(&self) -> Vec<Parser<'a>> {
+        let mut sections: Vec<Parser<'a>> = vec![];
+        let mut in_section = false;
+        for line in self.lines() {
+            if line.rest().trim().is_empty() {
+                in_section = false;
+                continue;
+            }
+
+            match sections.last_mut() {
+                Some(section) if in_section => section.end = line.end,
+                _ => sections.push(line),
+            }
+
+            in_section = true;
+        }
+
+        sections
+    }
+
+    /// Check that the whole input has been consumed
+    pub fn end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(format!("unexpected `{}`", c))),
+        }
+    }
+}
+
+/// Parse a grid made of one line per row, `cell` giving the value of each character (or `None` if
+/// the character is invalid). All rows must have the same length.
+pub fn grid<'a, T>(
+    lines: impl IntoIterator<Item = Parser<'a>>,
+    cell: impl Fn(char) -> Option<T>,
+) -> Result<Grid<T>, ParseError> {
+    let mut rows: Vec<Vec<T>> = vec![];
+    for mut line in lines {
+        let row = line.cells(&cell)?;
+
+        if let Some(first_row) = rows.first()
+            && first_row.len() != row.len()
+        {
+            return Err(line.error(format!(
+                "expected {} cells like the first row, found {}",
+                first_row.len(),
+                row.len()
+            )));
+        }
+
+        rows.push(row);
+    }
+
+    Ok(Grid::new(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_primitives() {
+        let mut parser = Parser::new("12,-3,+4 5-8");
+        let values = parser.separated(",", |p| p.signed::<i64>()).unwrap();
+        assert_eq!(values, vec![12, -3, 4]);
+
+        parser.skip_spaces();
+        assert_eq!(parser.range().unwrap(), Range::new(5, 8));
+        assert!(parser.end().is_ok());
+
+        let mut parser = Parser::new("fF-1z0");
+        assert_eq!(parser.range_radix(16).unwrap(), Range::new(255, 1));
+        assert_eq!(parser.unsigned_radix(36).unwrap(), 35 * 36);
+        assert!(Parser::new("2").unsigned_radix(2).is_err());
+        for radix in [0, 1, 37, u32::MAX] {
+            let error = Parser::new("1").unsigned_radix(radix).unwrap_err();
+            assert_eq!(error.column, 1);
+        }
+        let error = Parser::new("5-x").range().unwrap_err();
+        assert_eq!(error.message, "expected an unsigned integer");
+    }
+
+    #[test]
+    fn check_delimited() {
+        let mut parser = Parser::new("[.#] (1,(2)) {3}");
+        assert_eq!(parser.delimited('[', ']').unwrap().rest(), ".#");
+        parser.skip_spaces();
+        assert_eq!(parser.delimited('(', ')').unwrap().rest(), "1,(2)");
+        parser.skip_spaces();
+        let mut content = parser.delimited('{', '}').unwrap();
+        assert_eq!(content.unsigned::<u8>(), Ok(3));
+        assert!(parser.is_empty());
+    }
+
+    #[test]
+    fn check_sections() {
+        let parser = Parser::new("1-2\r\n3-4\r\n  \r\n5\n6\n");
+        let sections = parser.sections();
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].rest(), "1-2\r\n3-4");
+
+        let lines: Vec<&str> = sections[1].lines().map(|line| line.rest()).collect();
+        assert_eq!(lines, vec!["5", "6"]);
+    }
+
+    #[test]
+    fn check_error_positions() {
+        let parser = Parser::new("1,2\n3;4\n");
+        let error = parser
+            .lines()
+            .map(|mut line| line.separated(",", |p| p.unsigned::<u32>()).and(line.end()))
+            .find_map(|result| result.err())
+            .unwrap();
+        assert_eq!((error.line, error.column), (2, 2));
+
+        let mut parser = Parser::at_line("[é 99999999999", 10);
+        parser.literal("[é ").unwrap();
+        let error = parser.unsigned::<u32>().unwrap_err();
+        assert_eq!((error.line, error.column), (10, 4));
+        assert_eq!(
+            error.to_string(),
+            "line 10, column 4: integer `99999999999` out of range"
+        );
+
+        let error = Parser::new("(ab").delimited('(', ')').unwrap_err();
+        assert_eq!((error.line, error.column), (1, 1));
+
+        let error = Parser::new("12,-3").bounded(0..=10).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 1: integer `12` out of range 0..=10"
+        );
+    }
+
+    #[test]
+    fn check_grid() {
+        let cell = |c| match c {
+            '.' => Some(false),
+            '#' => Some(true),
+            _ => None,
+        };
+
+        let grid = grid(Parser::new(".#\n#.\n").lines(), cell).unwrap();
+        assert_eq!(grid.rows, vec![vec![false, true], vec![true, false]]);
+
+        let error = super::grid(Parser::new(".#\n#").lines(), cell)
+            .err()
+            .unwrap();
+        assert_eq!((error.line, error.column), (2, 2));
+
+        let error = super::grid(Parser::new(".#\n#x").lines(), cell)
+            .err()
+            .unwrap();
+        assert_eq!(error.message, "unexpected `x`");
+    }
+}
@@ -0,0 +1,215 @@
+use crate::Rng;
+use std::fmt;
+
+// Bound the number of simplifications, in case a shrinking loops
+const MAX_SHRINK_STEPS: usize = 1000;
+
+/// Values which can be simplified, to report the simplest case on which a property fails
+pub trait Shrink: Sized {
+    /// Simpler values, most promising first
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! impl_shrink_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                // Towards zero by halving distances: zero itself, half the value, then closer and
+                // closer to it (3/4, 7/8...) down to one step closer, so that shrinking to the
+                // boundary of a property takes a logarithmic number of steps
+                fn shrink(&self) -> Vec<Self> {
+                    let mut values: Vec<Self> = vec![];
+                    let mut distance = *self;
+                    while distance != 0 {
+                        values.push(*self - distance);
+                        distance /= 2;
+                    }
+
+                    values
+                }
+            }
+        )*
+    };
+}
+
+impl_shrink_integer!(i32, i64, u32, u64, usize);
+
+impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let firsts = self.0.shrink().into_iter().map(|a| (a, self.1.clone()));
+        let seconds = self.1.shrink().into_iter().map(|b| (self.0.clone(), b));
+        firsts.chain(seconds).collect()
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone, C: Shrink + Clone> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<Self> {
+        let firsts = self
+            .0
+            .shrink()
+            .into_iter()
+            .map(|a| (a, self.1.clone(), self.2.clone()));
+        let seconds = self
+            .1
+            .shrink()
+            .into_iter()
+            .map(|b| (self.0.clone(), b, self.2.clone()));
+        let thirds = self
+            .2
+            .shrink()
+            .into_iter()
+            .map(|c| (self.0.clone(), self.1.clone(), c));
+        firsts.chain(seconds).chain(thirds).collect()
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    // Shorter vectors first (empty, first half, one item removed), then simpler items
+    fn shrink(&self) -> Vec<Self> {
+        if self.is_empty() {
+            return vec![];
+        }
+
+        let mut values = vec![vec![], self[..self.len() / 2].to_vec()];
+        for index in 0..self.len() {
+            let mut value = self.clone();
+            value.remove(index);
+            values.push(value);
+        }
+
+        for (index, item) in self.iter().enumerate() {
+            for simpler_item in item.shrink() {
+                let mut value = self.clone();
+                value[index] = simpler_item;
+                values.push(value);
+            }
+        }
+
+        values
+    }
+}
+
+/// Generated case on which a property does not hold
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Failure<T> {
+    pub seed: u64,
+    /// Case as generated
+    pub original: T,
+    /// Simplest failing case found by shrinking the original one
+    pub case: T,
+    pub message: String,
+}
+
+impl<T: fmt::Debug> fmt::Display for Failure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "property does not hold (seed {}): {}",
+            self.seed, self.message
+        )?;
+        writeln!(f, "  case:     {:?}", self.case)?;
+        write!(f, "  original: {:?}", self.original)
+    }
+}
+
+/// Check a property on cases generated for seeds from 0 to `seed_count`, returns the first failing
+/// case once shrunk. As shrinking may leave the generated domain (e.g. a position beyond the size
+/// of a dial), properties should hold for cases outside of it.
+pub fn find_failure<T: Shrink + Clone>(
+    seed_count: u64,
+    generate: impl Fn(&mut Rng) -> T,
+    property: impl Fn(&T) -> Result<(), String>,
+) -> Option<Failure<T>> {
+    for seed in 0..seed_count {
+        let original = generate(&mut Rng::new(seed));
+        let Err(mut message) = property(&original) else {
+            continue;
+        };
+
+        // Keep the first simpler case which still fails, until none does
+        let mut case = original.clone();
+        for _ in 0..MAX_SHRINK_STEPS {
+            let simpler = case
+                .shrink()
+                .into_iter()
+                .find_map(|candidate| property(&candidate).err().map(|m| (candidate, m)));
+            let Some((simpler_case, simpler_message)) = simpler else {
+                break;
+            };
+
+            case = simpler_case;
+            message = simpler_message;
+        }
+
+        return Some(Failure {
+            seed,
+            original,
+            case,
+            message,
+        });
+    }
+
+    None
+}
+
+/// Panic with the simplest case a property does not hold for, if any
+pub fn assert_property<T: Shrink + Clone + fmt::Debug>(
+    seed_count: u64,
+    generate: impl Fn(&mut Rng) -> T,
+    property: impl Fn(&T) -> Result<(), String>,
+) {
+    if let Some(failure) = find_failure(seed_count, generate, property) {
+        panic!("{}", failure);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_shrinking() {
+        assert_eq!(7_i32.shrink(), [0, 4, 6]);
+        assert_eq!(100_u32.shrink(), [0, 50, 75, 88, 94, 97, 99]);
+        assert_eq!((-1_i32).shrink(), [0]);
+        assert_eq!((-6_i64).shrink(), [0, -3, -5]);
+        assert_eq!(i32::MIN.shrink()[..2], [0, i32::MIN / 2]);
+        assert!(0_u64.shrink().is_empty());
+
+        let small = |value: &i32| {
+            if *value < 1000 {
+                Ok(())
+            } else {
+                Err(format!("{} is too large", value))
+            }
+        };
+        assert_property(100, |rng| rng.between(0, 999) as i32, small);
+
+        let failure = find_failure(100, |rng| rng.between(0, 100_000) as i32, small).unwrap();
+        assert_eq!(failure.case, 1000);
+        assert_eq!(failure.message, "1000 is too large");
+        assert!(failure.original >= 1000);
+
+        // Far from the boundary, within the bound on shrinking steps
+        let failure = find_failure(1, |_| i32::MAX, small).unwrap();
+        assert_eq!(failure.case, 1000);
+
+        // A single item is enough to fail
+        let sum_below_10 = |values: &Vec<(u32, u32)>| {
+            let sum: u32 = values.iter().map(|(a, b)| a + b).sum();
+            if sum < 10 {
+                Ok(())
+            } else {
+                Err(format!("sum is {}", sum))
+            }
+        };
+        let generate = |rng: &mut Rng| {
+            (0..20)
+                .map(|_| (rng.between(0, 5) as u32, rng.between(0, 5) as u32))
+                .collect()
+        };
+        let failure = find_failure(10, generate, sum_below_10).unwrap();
+        assert_eq!(failure.message, "sum is 10");
+        assert!(failure.case.len() <= 2);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::args::{Args, ArgsError};
+
+/// Seeded pseudo-random number generator (SplitMix64), so that generated inputs can be reproduced
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    /// Random value between `min` and `max` (inclusive)
+    pub fn between(&mut self, min: usize, max: usize) -> usize {
+        assert!(min <= max, "empty range {}..={}", min, max);
+        let span = (max - min) as u128 + 1;
+        min + ((self.next_u64() as u128 * span) >> 64) as usize
+    }
+
+    /// Return `true` with the given probability
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.between(0, items.len() - 1)]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.between(0, index));
+        }
+    }
+}
+
+/// Print a generated input if `--generate` is given on the command line (with optional `--seed=N`
+/// and `--size=N`), returns `false` if the input should be solved as usual
+pub fn print_generated(
+    args: &Args,
+    generate: impl Fn(&mut Rng, usize) -> String,
+) -> Result<bool, ArgsError> {
+    if !args.flag("generate") {
+        return Ok(false);
+    }
+
+    let seed = args.value_or("seed", 0)?;
+    let size = args.value_or("size", 10)?;
+    print!("{}", generate(&mut Rng::new(seed), size));
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_reproducible() {
+        let values: Vec<u64> = (0..4).map(|_| Rng::new(42).next_u64()).collect();
+        assert!(values.iter().all(|value| *value == values[0]));
+
+        let mut rng = Rng::new(42);
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+
+    #[test]
+    fn check_between() {
+        let mut rng = Rng::new(7);
+        let mut seen = [false; 5];
+        for _ in 0..1000 {
+            let value = rng.between(10, 14);
+            seen[value - 10] = true;
+        }
+        assert!(seen.iter().all(|seen| *seen));
+        assert_eq!(rng.between(3, 3), 3);
+        rng.between(0, usize::MAX); // Must not overflow
+
+        let mut items = [1, 2, 3, 4, 5];
+        rng.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, [1, 2, 3, 4, 5]);
+    }
+}
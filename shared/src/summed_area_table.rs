@@ -0,0 +1,99 @@
+use crate::{Grid, Vector};
+
+/// 2D prefix sums of a grid, giving the sum of any rectangle in constant time.
+///
+/// `sums[y][x]` holds the sum of all cells strictly above and left of `(x, y)`, hence the extra
+/// leading row and column of zeros.
+pub struct SummedAreaTable {
+    size: Vector,
+    sums: Vec<i64>,
+}
+
+impl SummedAreaTable {
+    /// Build the table by mapping each cell of the grid to a value
+    pub fn new<TCell>(grid: &Grid<TCell>, value: impl Fn(&TCell) -> i64) -> SummedAreaTable {
+        let size = *grid.size();
+        let stride = size.x as usize + 1;
+        let mut sums = vec![0; stride * (size.y as usize + 1)];
+
+        for (y, row) in grid.rows.iter().enumerate() {
+            let mut row_sum = 0;
+            for (x, cell) in row.iter().enumerate() {
+                row_sum += value(cell);
+                sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row_sum;
+            }
+        }
+
+        SummedAreaTable { size, sums }
+    }
+
+    /// Build the table counting the cells matching a predicate
+    pub fn count<TCell>(grid: &Grid<TCell>, predicate: impl Fn(&TCell) -> bool) -> SummedAreaTable {
+        Self::new(grid, |cell| predicate(cell) as i64)
+    }
+
+    pub fn size(&self) -> &Vector {
+        &self.size
+    }
+
+    /// Sum of the rectangle between two corners (both included). The rectangle is clamped to the
+    /// grid boundaries.
+    pub fn sum(&self, corner1: &Vector, corner2: &Vector) -> i64 {
+        let min_x = corner1.x.min(corner2.x).max(0);
+        let min_y = corner1.y.min(corner2.y).max(0);
+        let max_x = corner1.x.max(corner2.x).min(self.size.x - 1);
+        let max_y = corner1.y.max(corner2.y).min(self.size.y - 1);
+        if min_x > max_x || min_y > max_y {
+            return 0;
+        }
+
+        let at = |x: i64, y: i64| self.sums[y as usize * (self.size.x as usize + 1) + x as usize];
+        at(max_x + 1, max_y + 1) - at(min_x, max_y + 1) - at(max_x + 1, min_y) + at(min_x, min_y)
+    }
+
+    /// Check if all cells of the rectangle between two corners (both included) are counted,
+    /// for a table built with [`SummedAreaTable::count`]
+    pub fn is_full(&self, corner1: &Vector, corner2: &Vector) -> bool {
+        let width = (corner1.x - corner2.x).abs() + 1;
+        let height = (corner1.y - corner2.y).abs() + 1;
+        self.sum(corner1, corner2) == width * height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Grid<i64> {
+        Grid::new(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]])
+    }
+
+    #[test]
+    fn check_sum() {
+        let table = SummedAreaTable::new(&grid(), |value| *value);
+        assert_eq!(
+            table.sum(&Vector { x: 0, y: 0 }, &Vector { x: 2, y: 2 }),
+            45
+        );
+        assert_eq!(
+            table.sum(&Vector { x: 2, y: 2 }, &Vector { x: 1, y: 1 }),
+            28
+        );
+        assert_eq!(
+            table.sum(&Vector { x: 1, y: 0 }, &Vector { x: 1, y: 2 }),
+            15
+        );
+        assert_eq!(
+            table.sum(&Vector { x: -5, y: 2 }, &Vector { x: 0, y: 9 }),
+            7
+        );
+    }
+
+    #[test]
+    fn check_is_full() {
+        let table = SummedAreaTable::count(&grid(), |value| *value != 5);
+        assert!(table.is_full(&Vector { x: 0, y: 0 }, &Vector { x: 2, y: 0 }));
+        assert!(!table.is_full(&Vector { x: 0, y: 0 }, &Vector { x: 1, y: 1 }));
+        assert!(!table.is_full(&Vector { x: 2, y: 2 }, &Vector { x: 2, y: 3 }));
+    }
+}
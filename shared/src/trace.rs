@@ -0,0 +1,112 @@
+use crate::args::Args;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// Intermediate result emitted by a solver, to explain how an answer was found
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Event {
+    pub name: &'static str,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Event {
+    /// Value of a field, if any
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field_name, _)| *field_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for (name, value) in &self.fields {
+            write!(f, " {}={}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+type Sink = Box<dyn FnMut(Event)>;
+
+thread_local! {
+    // Receives the events of the current thread, events are dropped without any sink. Spawned
+    // threads start without a sink, so events emitted by worker threads are never received.
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Check if events are received, so that solvers can skip computing values only used for tracing
+pub fn enabled() -> bool {
+    SINK.with(|sink| sink.borrow().is_some())
+}
+
+/// Emit an event with some named values, values are only formatted if events are received
+pub fn event(name: &'static str, fields: &[(&'static str, &dyn fmt::Display)]) {
+    SINK.with(|sink| {
+        if let Some(sink) = sink.borrow_mut().as_mut() {
+            sink(Event {
+                name,
+                fields: fields
+                    .iter()
+                    .map(|(name, value)| (*name, value.to_string()))
+                    .collect(),
+            });
+        }
+    });
+}
+
+/// Send the events of the current thread to a sink (or drop them with `None`), returns the
+/// previous sink
+pub fn set_sink(sink: Option<Sink>) -> Option<Sink> {
+    SINK.with(|current| current.replace(sink))
+}
+
+/// Print events to stderr if `--verbose` is given on the command line. Only the events of the
+/// calling thread are printed, not those of the threads it spawns.
+pub fn enable_from_args(args: &Args) {
+    if args.flag("verbose") {
+        set_sink(Some(Box::new(|event| eprintln!("  [trace] {}", event))));
+    }
+}
+
+/// Run a function and collect the events it emits
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Event>) {
+    let events = Rc::new(RefCell::new(vec![]));
+    let sink_events = events.clone();
+    let previous = set_sink(Some(Box::new(move |event| {
+        sink_events.borrow_mut().push(event)
+    })));
+
+    let result = f();
+    set_sink(previous);
+
+    let events = events.borrow().clone();
+    (result, events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_capture() {
+        event("dropped", &[]);
+        assert!(!enabled());
+
+        let (result, events) = capture(|| {
+            assert!(enabled());
+            event("merge", &[("id1", &3), ("id2", &"b")]);
+            42
+        });
+
+        assert_eq!(result, 42);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].to_string(), "merge id1=3 id2=b");
+        assert_eq!(events[0].field("id2"), Some("b"));
+        assert!(!enabled());
+    }
+}
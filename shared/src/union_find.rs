@@ -0,0 +1,120 @@
+/// Disjoint sets of elements `0..len`, with path compression and union by size
+pub struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    component_count: usize,
+}
+
+impl UnionFind {
+    /// Create `len` components containing a single element each
+    pub fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            component_count: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.component_count
+    }
+
+    /// Get the representative element of the component containing `element`
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        // Path compression: attach all elements on the path directly to the root
+        let mut current = element;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// Merge the components containing both elements, returns `false` if they were already
+    /// in the same component
+    pub fn union(&mut self, element1: usize, element2: usize) -> bool {
+        let mut root1 = self.find(element1);
+        let mut root2 = self.find(element2);
+        if root1 == root2 {
+            return false;
+        }
+
+        // Attach the smallest tree below the largest one
+        if self.sizes[root1] < self.sizes[root2] {
+            std::mem::swap(&mut root1, &mut root2);
+        }
+
+        self.parents[root2] = root1;
+        self.sizes[root1] += self.sizes[root2];
+        self.component_count -= 1;
+        true
+    }
+
+    pub fn is_connected(&mut self, element1: usize, element2: usize) -> bool {
+        self.find(element1) == self.find(element2)
+    }
+
+    /// Number of elements in the component containing `element`
+    pub fn component_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+
+    /// All elements of the component containing `element`
+    pub fn members(&mut self, element: usize) -> Vec<usize> {
+        let root = self.find(element);
+        (0..self.len())
+            .filter(|other| self.find(*other) == root)
+            .collect()
+    }
+
+    /// All components, each one listing its elements in increasing order
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut components_by_root: Vec<Vec<usize>> = vec![vec![]; self.len()];
+        for element in 0..self.len() {
+            let root = self.find(element);
+            components_by_root[root].push(element);
+        }
+
+        components_by_root
+            .into_iter()
+            .filter(|component| !component.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_union_and_find() {
+        let mut sets = UnionFind::new(6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+
+        assert_eq!(sets.component_count(), 3);
+        assert_eq!(sets.component_size(3), 4);
+        assert!(sets.is_connected(0, 3));
+        assert!(!sets.is_connected(0, 4));
+        assert_eq!(sets.members(2), vec![0, 1, 2, 3]);
+        assert_eq!(sets.components(), vec![vec![0, 1, 2, 3], vec![4], vec![5]]);
+    }
+}